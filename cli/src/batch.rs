@@ -0,0 +1,80 @@
+use std::io::Read;
+
+use clio::Input;
+
+use gpd_linuxcontrols::controls_field::registry::Assignment;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::protocol::function::read_config;
+use gpd_linuxcontrols::protocol::Device;
+
+use crate::diff::print_changes;
+use crate::helper::write_and_save;
+
+/// Assignments given on the command line, `PATH VALUE` is accepted for a single one
+pub(crate) fn from_args(args: Vec<String>) -> Result<Vec<Assignment>, Error> {
+    let args = match args.as_slice() {
+        [path, value] if !path.contains('=') => vec![format!("{}={}", path, value)],
+        _ => args,
+    };
+    parse(args.iter().map(|arg| (None, arg.as_str())))
+}
+
+/// Assignments of a batch file, skipping empty lines and `#` comments
+pub(crate) fn from_input(mut input: Input) -> Result<Vec<Assignment>, Error> {
+    let mut content = String::new();
+    input
+        .read_to_string(&mut content)
+        .map_err(|e| Error::from(e).context(input.path()))?;
+    parse(
+        content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (Some(i + 1), line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#')),
+    )
+}
+
+/// Parse every assignment first, so all mistakes are reported at once and nothing is written
+fn parse<'a>(
+    lines: impl Iterator<Item = (Option<usize>, &'a str)>,
+) -> Result<Vec<Assignment>, Error> {
+    let (assignments, errors): (Vec<_>, Vec<_>) = lines
+        .map(|(line, s)| {
+            s.parse::<Assignment>().map_err(|e| match line {
+                Some(line) => e.context(format!("line {}", line)),
+                None => e,
+            })
+        })
+        .partition(Result::is_ok);
+    let errors: Vec<Error> = errors.into_iter().filter_map(Result::err).collect();
+    if let Some(first) = errors.first() {
+        return Err(Error {
+            message: errors
+                .iter()
+                .map(|e| e.message.as_str())
+                .collect::<Vec<&str>>()
+                .join("\n"),
+            ..first.clone()
+        });
+    }
+    Ok(assignments.into_iter().filter_map(Result::ok).collect())
+}
+
+/// Apply all assignments to the device config in memory, then write and save it once
+pub(crate) fn run(
+    device: &Device,
+    assignments: Vec<Assignment>,
+    force: bool,
+    dry_run: bool,
+) -> Result<(), Error> {
+    let current = read_config(device)?;
+    let mut config = current;
+    assignments
+        .iter()
+        .try_for_each(|assignment| assignment.apply(&mut config))?;
+    if dry_run {
+        print_changes(&current.diff(&config));
+        return Ok(());
+    }
+    write_and_save(device, config, force)
+}
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+
+use gpd_linuxcontrols::controls_field::hid_usage_id_u8::HIDUsageIDu8;
+use gpd_linuxcontrols::controls_field::registry::{FieldKind, FieldValue, FIELDS};
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+
+use crate::layout::{Sequence, BACK_BUTTONS};
+
+const WIDTH: u32 = 1000;
+const HEIGHT: u32 = 560;
+const CHIP_WIDTH: u32 = 150;
+const CHIP_HEIGHT: u32 = 26;
+const DELAY_WIDTH: u32 = 80;
+
+/// Printable diagram of the controls with their keys, back button macros and delays
+pub(crate) fn svg(title: &str, description: Option<&str>, config: &ControlsConfig) -> String {
+    let km = &config.keyboard_mouse;
+    let pad = &km.directional_pad;
+    let stick = &km.left_stick;
+    let mut s = String::new();
+    let _ = write!(
+        s,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {WIDTH} {HEIGHT}" width="{WIDTH}" height="{HEIGHT}" font-family="sans-serif">
+<rect width="{WIDTH}" height="{HEIGHT}" fill="white"/>
+<text x="{}" y="36" font-size="24" font-weight="bold" text-anchor="middle">{}</text>
+"#,
+        WIDTH / 2,
+        escape(title)
+    );
+    if let Some(description) = description {
+        let _ = writeln!(
+            s,
+            r##"<text x="{}" y="60" font-size="14" fill="#555" text-anchor="middle">{}</text>"##,
+            WIDTH / 2,
+            escape(description)
+        );
+    }
+    BACK_BUTTONS
+        .iter()
+        .zip([100, 140])
+        .for_each(|(button, y)| back_button(&mut s, y, config, button));
+    let _ = writeln!(
+        s,
+        r##"<rect x="10" y="180" width="{}" height="320" rx="60" fill="#f2f2f2" stroke="#999" stroke-width="2"/>"##,
+        WIDTH - 20
+    );
+    cross(
+        &mut s,
+        (250, 260),
+        "Left stick",
+        [
+            ("↑", stick.up),
+            ("↓", stick.down),
+            ("←", stick.left),
+            ("→", stick.right),
+        ],
+        Some(("●", stick.push)),
+    );
+    cross(
+        &mut s,
+        (750, 260),
+        "ABXY",
+        [("Y", km.y), ("A", km.a), ("X", km.x), ("B", km.b)],
+        None,
+    );
+    cross(
+        &mut s,
+        (250, 420),
+        "D-pad",
+        [
+            ("↑", pad.up),
+            ("↓", pad.down),
+            ("←", pad.left),
+            ("→", pad.right),
+        ],
+        None,
+    );
+    label(&mut s, (750, 420 - CHIP_HEIGHT / 2 - 34), "Right stick");
+    chip(&mut s, (750, 420), "●", km.right_stick_push);
+    let _ = writeln!(
+        s,
+        r##"<text x="{}" y="{}" font-size="13" fill="#333" text-anchor="middle">{}</text>"##,
+        WIDTH / 2,
+        HEIGHT - 30,
+        escape(&settings(config).join("   "))
+    );
+    s.push_str("</svg>\n");
+    s
+}
+
+/// Standalone page with the [svg] diagram and a table of every field
+pub(crate) fn html(title: &str, description: Option<&str>, config: &ControlsConfig) -> String {
+    let rows: String = FIELDS
+        .iter()
+        .map(|field| {
+            format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                field.path,
+                escape(&field.get(config).to_string()),
+                escape(field.description)
+            )
+        })
+        .collect();
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+@page {{ size: A4 landscape; margin: 1cm; }}
+body {{ font-family: sans-serif; margin: 0 auto; max-width: 1000px; }}
+svg {{ width: 100%; height: auto; }}
+table {{ border-collapse: collapse; width: 100%; font-size: 12px; break-before: page; }}
+td, th {{ border: 1px solid #ccc; padding: 2px 6px; text-align: left; }}
+</style>
+</head>
+<body>
+{svg}<table>
+<tr><th>Field</th><th>Value</th><th>Description</th></tr>
+{rows}</table>
+</body>
+</html>
+"#,
+        title = escape(title),
+        svg = svg(title, description, config),
+    )
+}
+
+fn name(key: HIDUsageIDu8) -> String {
+    FieldValue::Key(key).to_string()
+}
+
+/// A button with its caption and assigned key, centered on `(x, y)`
+fn chip(s: &mut String, (x, y): (u32, u32), caption: &str, key: HIDUsageIDu8) {
+    let name = name(key);
+    // Long names like keyboard-left-control don't fit otherwise
+    let font_size = if name.len() > 16 { 10 } else { 12 };
+    let _ = writeln!(
+        s,
+        r##"<rect x="{}" y="{}" width="{CHIP_WIDTH}" height="{CHIP_HEIGHT}" rx="6" fill="white" stroke="#333"/><text x="{x}" y="{}" font-size="{font_size}" text-anchor="middle"><tspan font-weight="bold">{}</tspan> <tspan font-family="monospace">{}</tspan></text>"##,
+        x - CHIP_WIDTH / 2,
+        y - CHIP_HEIGHT / 2,
+        y + 4,
+        escape(caption),
+        escape(&name)
+    );
+}
+
+fn label(s: &mut String, (x, y): (u32, u32), text: &str) {
+    let _ = writeln!(
+        s,
+        r#"<text x="{x}" y="{y}" font-size="14" font-weight="bold" text-anchor="middle">{}</text>"#,
+        escape(text)
+    );
+}
+
+/// Up, down, left and right around an optional center
+fn cross(
+    s: &mut String,
+    (x, y): (u32, u32),
+    title: &str,
+    [up, down, left, right]: [(&str, HIDUsageIDu8); 4],
+    center: Option<(&str, HIDUsageIDu8)>,
+) {
+    let dx = CHIP_WIDTH + 6;
+    let dy = CHIP_HEIGHT + 6;
+    label(s, (x, y - dy - CHIP_HEIGHT / 2 - 8), title);
+    chip(s, (x, y - dy), up.0, up.1);
+    chip(s, (x, y + dy), down.0, down.1);
+    chip(s, (x - dx, y), left.0, left.1);
+    chip(s, (x + dx, y), right.0, right.1);
+    if let Some((caption, key)) = center {
+        chip(s, (x, y), caption, key);
+    }
+}
+
+/// The keys of a back button with the delays between them, trailing unassigned keys are left out
+fn back_button(s: &mut String, y: u32, config: &ControlsConfig, button: &Sequence) {
+    let _ = writeln!(
+        s,
+        r#"<text x="30" y="{}" font-size="16" font-weight="bold">{}</text>"#,
+        y + 5,
+        button.label
+    );
+    let start = 80 + CHIP_WIDTH / 2;
+    let used = button.used(config);
+    button.keys[..used].iter().enumerate().for_each(|(i, key)| {
+        let x = start + i as u32 * (CHIP_WIDTH + DELAY_WIDTH);
+        if let FieldValue::Key(key) = key.get(config) {
+            chip(s, (x, y), &(i + 1).to_string(), key);
+        }
+        if i + 1 < used {
+            let _ = writeln!(
+                s,
+                r##"<text x="{}" y="{}" font-size="12" fill="#555" text-anchor="middle">{} →</text>"##,
+                x + (CHIP_WIDTH + DELAY_WIDTH) / 2,
+                y + 4,
+                button.delays[i].get(config)
+            );
+        }
+    });
+}
+
+/// Vibration and dead zones, which have no place in the diagram
+fn settings(config: &ControlsConfig) -> Vec<String> {
+    FIELDS
+        .iter()
+        .filter(|field| matches!(field.kind, FieldKind::Vibrate | FieldKind::DeadZone))
+        .map(|field| format!("{}: {}", field.path, field.get(config)))
+        .collect()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,21 +1,85 @@
-use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
 use clap_verbosity_flag::Verbosity;
 use clio::{ClioPath, Input};
 
+use gpd_linuxcontrols::controls_field::back_button::BackButtonDelay as BackButtonDelayValue;
+use gpd_linuxcontrols::controls_field::dead_zones::DeadZone as DeadZoneValue;
+use gpd_linuxcontrols::controls_field::format::ConfigFormat;
 use gpd_linuxcontrols::controls_field::hid_usage_id_u8::HIDUsageIDu8;
 use gpd_linuxcontrols::enums::hid_usage_id::HIDUsageID;
 use gpd_linuxcontrols::enums::{BackButton, BackButtonDelay, DeadZone, KeyboardMouse, Vibrate};
+use gpd_linuxcontrols::error::Error;
 use gpd_linuxcontrols::parse_hex;
 
+use crate::error::exit_codes_help;
+
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None, display_name = "gpd-controls")]
+#[command(author, version, about, long_about = None, display_name = "gpd-controls", after_long_help = exit_codes_help())]
 pub struct Cli {
     #[command(subcommand)]
     pub(crate) command: Commands,
 
     #[command(flatten)]
     pub(crate) verbose: Verbosity,
+
+    #[command(flatten)]
+    pub(crate) transfer: TransferArgs,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Output format of read commands [default: json], json and json-compact also print errors as JSON on stderr"
+    )]
+    pub(crate) output: Option<OutputFormat>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum OutputFormat {
+    Json,
+    JsonCompact,
+    Yaml,
+    Toml,
+    #[value(help = "Aligned dotted paths and values")]
+    Table,
+    #[value(help = "Shell-sourceable KEY=value lines")]
+    Env,
+}
+
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Transfer")]
+pub(crate) struct TransferArgs {
+    #[arg(
+        long,
+        global = true,
+        value_name = "MS",
+        help = "Timeout of a single control transfer in milliseconds [default: 1000]"
+    )]
+    pub(crate) timeout: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Times to retry a failed command to the device [default: 2]"
+    )]
+    pub(crate) retries: Option<u8>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "MS",
+        help = "Delay between a request and reading its response in milliseconds [default: 5]"
+    )]
+    pub(crate) command_delay: Option<u64>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Accept responses whose header does not match the request instead of retrying"
+    )]
+    pub(crate) no_header_retry: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -37,6 +101,255 @@ pub(crate) enum Commands {
         )]
         force: bool,
     },
+    #[command(about = "Print a field by its dotted path, or all fields")]
+    Get {
+        #[arg(help = "e.g. back_button.left.first, or a unique suffix like left_stick.up")]
+        path: Option<String>,
+    },
+    #[command(
+        about = "Set fields by their dotted path, see `fields` for paths and values",
+        long_about = "Set fields by their dotted path, see `fields` for paths and values. \
+All assignments are applied to the config at once and written with a single save, \
+e.g. gpd-controls set a=enter b=escape back_button.left.first=f13"
+    )]
+    Set {
+        #[arg(
+            required = true,
+            allow_negative_numbers = true,
+            value_name = "PATH=VALUE",
+            help = "Assignments like back_button.left.first=f13, a unique suffix like a=enter works too, `set PATH VALUE` sets a single field"
+        )]
+        assignments: Vec<String>,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+
+        #[arg(long, help = "Print the changes instead of writing them")]
+        dry_run: bool,
+    },
+    #[command(
+        about = "Apply assignments from a file, one PATH=VALUE per line, with a single save",
+        long_about = "Apply assignments from a file, one PATH=VALUE per line, with a single save. \
+Empty lines and lines starting with # are ignored. \
+Every line is checked before anything is written."
+    )]
+    Batch {
+        #[arg(long, default_value = "-", help = "Use '-' for stdin", value_parser)]
+        file: Input,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+
+        #[arg(long, help = "Print the changes instead of writing them")]
+        dry_run: bool,
+    },
+    #[command(about = "List path, allowed values and description of every field")]
+    Fields,
+    #[command(
+        about = "Apply a JSON merge patch (RFC 7396) or JSON Patch (RFC 6902) to the device config"
+    )]
+    Patch {
+        #[arg(
+            long,
+            help = "Read the patch from a file, use '-' for stdin, an object is applied as merge patch, an array as JSON Patch",
+            value_parser
+        )]
+        file: Input,
+
+        #[arg(long, help = "Ignore unknown fields instead of rejecting them")]
+        lenient: bool,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+
+        #[arg(long, help = "Print the patched config instead of writing it")]
+        dry_run: bool,
+    },
+    #[command(
+        about = "Compare two configs field by field",
+        long_about = "Compare two configs field by field. \
+Each side is `device` for the live device, a JSON, TOML or YAML file, or the name of a profile or preset. \
+Use `file:<path>` or `profile:<name>` if a name is ambiguous."
+    )]
+    Diff {
+        #[arg(value_parser = parse_config_source)]
+        a: ConfigSource,
+
+        #[arg(value_parser = parse_config_source)]
+        b: ConfigSource,
+
+        #[arg(long, help = "Print the changes as JSON")]
+        json: bool,
+    },
+    #[command(
+        about = "Export configurable fields as JSON, TOML or YAML, or as printable cheat sheet"
+    )]
+    Export {
+        #[arg(
+            long,
+            help = "Write to a file instead of stdout, comments of an existing TOML file are kept"
+        )]
+        file: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Output format, detected by extension if omitted, JSON for stdout"
+        )]
+        format: Option<ExportFormat>,
+
+        #[arg(
+            long,
+            conflicts_with = "image",
+            help = "Export a stored profile or preset instead of the device config"
+        )]
+        profile: Option<String>,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["file", "format"],
+            help = "Print a short share code instead, e.g. GPD1:..."
+        )]
+        code: bool,
+
+        #[arg(long, requires = "code", help = "Also print the share code as QR code")]
+        qr: bool,
+
+        #[arg(
+            long,
+            requires = "file",
+            conflicts_with_all = ["format", "code"],
+            help = "Write the binary image of the write pages Minor0 to Minor7 to the file",
+            long_help = "Write the binary image of the write pages Minor0 to Minor7 to the file. \
+Reads only return the first 16 bytes of every 25 byte page, bytes 16 to 24 are written as zero."
+        )]
+        image: bool,
+    },
+    #[command(about = "Apply a share code created by `export --code`")]
+    Import {
+        #[arg(long)]
+        code: String,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+
+        #[arg(long, help = "Print the decoded config instead of writing it")]
+        dry_run: bool,
+    },
+    #[command(
+        about = "Store firmware versions, config, checksum and raw pages of the device in a file"
+    )]
+    Backup {
+        file: PathBuf,
+
+        #[arg(long, help = "File format, detected by extension if omitted")]
+        format: Option<ConfigFormat>,
+    },
+    #[command(about = "Write the raw pages of a backup back to the device")]
+    Restore {
+        file: PathBuf,
+
+        #[arg(long, help = "File format, detected by extension if omitted")]
+        format: Option<ConfigFormat>,
+
+        #[arg(
+            long,
+            help = "Restore even if the device runs other firmware than the backup was taken from"
+        )]
+        force: bool,
+    },
+    #[command(
+        about = "Configs saved before every change, in $XDG_STATE_HOME/gpd-controls/history"
+    )]
+    History {
+        #[command(subcommand)]
+        history_command: HistoryCommand,
+    },
+    #[command(about = "Restore the config saved before the last change")]
+    Undo,
+    #[command(about = "Manage named profiles in $XDG_CONFIG_HOME/gpd-controls/profiles")]
+    Profile {
+        #[command(subcommand)]
+        profile_command: ProfileCommand,
+    },
+    #[command(about = "Built-in presets, usable as base of profiles")]
+    Preset {
+        #[command(subcommand)]
+        preset_command: PresetCommand,
+    },
+    #[command(
+        about = "Switch profiles automatically while matching applications run",
+        long_about = "Watch running processes and apply the profile of the first matching rule, \
+the default profile while no rule matches. \
+The device is only opened while switching, so keyboard and mouse keep working in between."
+    )]
+    Daemon {
+        #[arg(
+            long,
+            help = "Rules file [default: $XDG_CONFIG_HOME/gpd-controls/autoswitch.toml]"
+        )]
+        rules: Option<PathBuf>,
+
+        #[arg(long, help = "Only log the switches, don't touch the device")]
+        dry_run: bool,
+    },
+    #[command(
+        about = "Apply a profile for the lifetime of a command",
+        long_about = "Apply a profile, run the command and restore the previous config when it exits. \
+Signals are forwarded to the command. \
+Use as Steam launch option: gpd-controls run --profile doom -- %command%"
+    )]
+    Run {
+        #[arg(long, help = "Name of a stored profile or a preset")]
+        profile: String,
+
+        #[arg(
+            long,
+            help = "Save to ROM while running, for firmware which ignores unsaved configs"
+        )]
+        save: bool,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+
+        #[arg(last = true, required = true, help = "Command to run")]
+        command: Vec<String>,
+    },
+    #[command(
+        about = "Edit the device config in $EDITOR and apply it after confirmation",
+        long_about = "Open the device config as commented document in $VISUAL or $EDITOR. \
+An invalid document is opened again with the errors on top, an empty one cancels the edit. \
+The changes are shown and applied after confirmation."
+    )]
+    Edit {
+        #[arg(long, default_value = "toml", help = "Format of the document")]
+        format: ConfigFormat,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+
+        #[arg(long, short, help = "Apply without asking for confirmation")]
+        yes: bool,
+    },
+    #[command(
+        about = "Edit a config interactively in the terminal",
+        long_about = "Edit the device config, a config file or a stored profile interactively in the terminal. \
+Changes are only written on `w`. \
+The device is only opened to read and to write, so keyboard and mouse keep working in between."
+    )]
+    Tui {
+        #[arg(
+            long,
+            conflicts_with = "profile",
+            help = "Edit a JSON, TOML or YAML file instead of the device"
+        )]
+        file: Option<PathBuf>,
+
+        #[arg(long, help = "Edit a stored profile instead of the device")]
+        profile: Option<String>,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+    },
     #[command(about = "Print HID Usage ID table")]
     HIDUsageID,
     #[command(about = "Reset All config field to 0 / No Function")]
@@ -63,6 +376,19 @@ pub(crate) enum Commands {
     },
 }
 
+#[derive(Subcommand, Debug, Eq, PartialEq)]
+pub(crate) enum HistoryCommand {
+    #[command(about = "List saved configs, 1 is the newest")]
+    List,
+    #[command(about = "Print a saved config")]
+    Show {
+        n: usize,
+
+        #[arg(long, default_value = "json")]
+        format: ConfigFormat,
+    },
+}
+
 #[derive(Subcommand, Debug, Eq, PartialEq)]
 pub(crate) enum ReadCommand {
     #[command(about = "Reset all fields")]
@@ -71,7 +397,14 @@ pub(crate) enum ReadCommand {
     Config,
     #[command(about = "Read Firmware versions")]
     Firmware,
-    KeyboardMouse,
+    KeyboardMouse {
+        #[arg(
+            long,
+            conflicts_with = "output",
+            help = "Print JSON or a diagram [default: json], other formats are chosen by --output"
+        )]
+        format: Option<KeyboardMouseFormat>,
+    },
     BackButton,
     Vibrate,
     DeadZones,
@@ -79,12 +412,55 @@ pub(crate) enum ReadCommand {
     Checksum,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ExportFormat {
+    Json,
+    Toml,
+    Yaml,
+    #[value(help = "Cheat sheet with a labelled controller diagram")]
+    Svg,
+    #[value(help = "Printable cheat sheet page with the diagram and a table of all fields")]
+    Html,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum KeyboardMouseFormat {
+    Json,
+    #[value(help = "Diagram of the controls with their keys, including the back buttons")]
+    Layout,
+}
+
 #[derive(Subcommand, Debug)]
 pub(crate) enum WriteCommand {
-    #[command(about = "Write configurable fields as JSON")]
+    #[command(
+        about = "Write a binary page image created by `export --image` as it is, without validation",
+        long_about = "Write a binary page image created by `export --image` as it is, without validation. \
+Only pages 0, 1, 3, 4 and 5 are written, like `write config` does. Bytes 16 to 24 of every page \
+are zero in exported images, as the device never returns them, and are written as zero too."
+    )]
+    Image {
+        file: PathBuf,
+    },
+    #[command(about = "Write configurable fields as JSON, TOML or YAML")]
     Config {
-        #[arg(long, help = "Read JSON from a file, use '-' for stdin", value_parser)]
+        #[arg(
+            long,
+            help = "Read config from a file, use '-' for stdin",
+            value_parser
+        )]
         file: Input,
+
+        #[arg(
+            long,
+            help = "Format of the file, detected by extension if omitted, JSON for stdin"
+        )]
+        format: Option<ConfigFormat>,
+
+        #[arg(
+            long,
+            help = "Ignore unknown fields instead of rejecting them, for configs from newer versions"
+        )]
+        lenient: bool,
     },
     KeyboardMouse(KeyboardMouseArgs),
     BackButton(BackButtonArgs),
@@ -93,6 +469,75 @@ pub(crate) enum WriteCommand {
     BackButtonDelay(BackButtonDelayArgs),
 }
 
+#[derive(Subcommand, Debug, Eq, PartialEq)]
+pub(crate) enum ProfileCommand {
+    #[command(about = "Save the device config as profile")]
+    Save {
+        name: String,
+
+        #[arg(long, help = "Description stored in the profile")]
+        description: Option<String>,
+
+        #[arg(
+            long,
+            help = "Only store the fields which differ from this base profile"
+        )]
+        extends: Option<String>,
+
+        #[arg(long, default_value = "toml", help = "File format of the profile")]
+        format: ConfigFormat,
+
+        #[arg(long, help = "Replace an existing profile")]
+        overwrite: bool,
+    },
+    #[command(about = "Write a profile to the device")]
+    Apply {
+        name: String,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+    },
+    #[command(about = "List all profiles")]
+    List,
+    #[command(about = "Print a profile")]
+    Show {
+        name: String,
+
+        #[arg(
+            long,
+            help = "Print the config merged with all base profiles, each field annotated with the profile which set it"
+        )]
+        resolved: bool,
+
+        #[arg(long, default_value = "json", help = "Output format")]
+        format: ConfigFormat,
+    },
+    #[command(about = "Delete a profile")]
+    Rm { name: String },
+    #[command(about = "Rename a profile")]
+    Rename { from: String, to: String },
+}
+
+#[derive(Subcommand, Debug, Eq, PartialEq)]
+pub(crate) enum PresetCommand {
+    #[command(about = "Write a preset to the device")]
+    Apply {
+        name: String,
+
+        #[arg(long, help = "Ignore value legality check and force write")]
+        force: bool,
+    },
+    #[command(about = "List all presets")]
+    List,
+    #[command(about = "Print a preset")]
+    Show {
+        name: String,
+
+        #[arg(long, default_value = "json", help = "Output format")]
+        format: ConfigFormat,
+    },
+}
+
 #[derive(Subcommand, Debug, Eq, PartialEq)]
 pub(crate) enum ResetCommand {
     KeyboardMouse,
@@ -162,11 +607,38 @@ pub(crate) struct VibrateArgs {
 #[derive(Parser, Debug, Eq, PartialEq)]
 pub(crate) struct DeadZoneArgs {
     pub(crate) key: DeadZone,
-    pub(crate) value: i8,
+    #[arg(
+        allow_negative_numbers = true,
+        help = "Dead zone in percent, in range -10..=10"
+    )]
+    pub(crate) value: DeadZoneValue,
 }
 
 #[derive(Parser, Debug, Eq, PartialEq)]
 pub(crate) struct BackButtonDelayArgs {
     pub(crate) key: BackButtonDelay,
-    pub(crate) value: u8,
+    #[arg(help = "Delay like 0ms or 100ms, use raw:<byte> to write an unsupported value")]
+    pub(crate) value: BackButtonDelayValue,
+}
+
+/// One side of a [Commands::Diff]
+#[derive(Clone, Debug)]
+pub(crate) enum ConfigSource {
+    Device,
+    File(PathBuf),
+    Profile(String),
+}
+
+fn parse_config_source(s: &str) -> Result<ConfigSource, Error> {
+    if s == "device" {
+        Ok(ConfigSource::Device)
+    } else if let Some(path) = s.strip_prefix("file:") {
+        Ok(ConfigSource::File(PathBuf::from(path)))
+    } else if let Some(name) = s.strip_prefix("profile:") {
+        Ok(ConfigSource::Profile(name.to_string()))
+    } else if PathBuf::from(s).is_file() {
+        Ok(ConfigSource::File(PathBuf::from(s)))
+    } else {
+        Ok(ConfigSource::Profile(s.to_string()))
+    }
 }
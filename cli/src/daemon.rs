@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use std::thread::sleep;
+
+use gpd_linuxcontrols::autoswitch::process::processes;
+use gpd_linuxcontrols::autoswitch::Rules;
+use gpd_linuxcontrols::controls_field::parse::ParseMode;
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::log::{error, info};
+use gpd_linuxcontrols::profile::resolve::resolve;
+use gpd_linuxcontrols::profile::store::ProfileStore;
+use gpd_linuxcontrols::protocol::function::read_config;
+use gpd_linuxcontrols::protocol::policy::TransferPolicy;
+
+use crate::helper::{with_device, write_and_save};
+
+/// Poll running processes forever and apply the profile of the first matching rule
+///
+/// A failed switch is retried on the next poll, so a busy or unplugged device is not fatal.
+pub(crate) fn run(
+    rules: Option<PathBuf>,
+    dry_run: bool,
+    policy: TransferPolicy,
+) -> Result<(), Error> {
+    let store = ProfileStore::open_default()?;
+    let path = rules.map_or_else(Rules::default_path, Ok)?;
+    let rules = Rules::load(&path, ParseMode::Strict)?;
+    let matcher = rules.matcher()?;
+    info!("Loaded {} rules from {}", rules.rules.len(), path.display());
+
+    // Without a default profile the config found now is restored when no rule matches
+    let initial = match (&rules.default, dry_run) {
+        (None, false) => Some(with_device(policy, read_config)?),
+        _ => None,
+    };
+
+    // `None` until the first switch succeeded, then the applied profile, `Some(None)` for default
+    let mut applied: Option<Option<String>> = None;
+    loop {
+        let target = matcher
+            .select(&processes()?)
+            .map(|rule| rule.profile.clone());
+        if applied.as_ref() != Some(&target) {
+            let name = target.as_ref().or(rules.default.as_ref());
+            info!(
+                "Switch to {}",
+                name.map_or("initial config", |v| v.as_str())
+            );
+            match switch(&store, name, initial, dry_run, policy) {
+                Ok(()) => applied = Some(target),
+                Err(e) => error!("{}", e),
+            }
+        }
+        sleep(rules.interval);
+    }
+}
+
+fn switch(
+    store: &ProfileStore,
+    name: Option<&String>,
+    initial: Option<ControlsConfig>,
+    dry_run: bool,
+    policy: TransferPolicy,
+) -> Result<(), Error> {
+    // Resolved on every switch, so edited profiles are picked up without a restart
+    let config = match name {
+        Some(name) => Some(resolve(store, name, ParseMode::Strict)?.config),
+        None => initial,
+    };
+    match config {
+        Some(config) if !dry_run => {
+            with_device(policy, |device| write_and_save(device, config, false))
+        }
+        _ => Ok(()),
+    }
+}
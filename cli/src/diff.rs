@@ -0,0 +1,69 @@
+use std::fs::read_to_string;
+
+use gpd_linuxcontrols::colored::Colorize;
+use gpd_linuxcontrols::controls_field::diff::FieldChange;
+use gpd_linuxcontrols::controls_field::format::ConfigFormat;
+use gpd_linuxcontrols::controls_field::parse::ParseMode;
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::profile::resolve::resolve;
+use gpd_linuxcontrols::profile::store::ProfileStore;
+use gpd_linuxcontrols::protocol::function::read_config;
+use gpd_linuxcontrols::protocol::Device;
+
+use crate::cli::ConfigSource;
+
+impl ConfigSource {
+    pub(crate) fn needs_device(&self) -> bool {
+        matches!(self, ConfigSource::Device)
+    }
+
+    fn load(&self, device: Option<&Device>) -> Result<ControlsConfig, Error> {
+        match self {
+            ConfigSource::Device => {
+                read_config(device.ok_or_else(|| "This command needs the device".to_string())?)
+            }
+            ConfigSource::File(path) => {
+                let s = read_to_string(path).map_err(|e| Error::from(e).context(path.display()))?;
+                ControlsConfig::from_str_with(
+                    &s,
+                    ConfigFormat::resolve(None, path),
+                    ParseMode::Strict,
+                )
+                .map_err(|e| e.context(path.display()))
+            }
+            ConfigSource::Profile(name) => {
+                Ok(resolve(&ProfileStore::open_default()?, name, ParseMode::Strict)?.config)
+            }
+        }
+    }
+}
+
+pub(crate) fn run(
+    a: ConfigSource,
+    b: ConfigSource,
+    json: bool,
+    device: Option<&Device>,
+) -> Result<(), Error> {
+    let changes = a.load(device)?.diff(&b.load(device)?);
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&changes).map_err(|e| e.to_string())?
+        );
+        return Ok(());
+    }
+    print_changes(&changes);
+    Ok(())
+}
+
+pub(crate) fn print_changes(changes: &[FieldChange]) {
+    changes.iter().for_each(|change| {
+        println!(
+            "{:48}{} -> {}",
+            change.path,
+            change.old.to_string().red(),
+            change.new.to_string().green()
+        )
+    });
+}
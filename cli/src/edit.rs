@@ -0,0 +1,168 @@
+use std::env::{temp_dir, var};
+use std::fs::{read_to_string, remove_file, write};
+use std::io::{stdin, stdout, Write};
+use std::path::Path;
+use std::process::Command;
+
+use gpd_linuxcontrols::controls_field::format::ConfigFormat;
+use gpd_linuxcontrols::controls_field::parse::ParseMode;
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::log::info;
+use gpd_linuxcontrols::protocol::function::read_config;
+use gpd_linuxcontrols::protocol::policy::TransferPolicy;
+
+use crate::diff::print_changes;
+use crate::helper::{with_device, write_and_save};
+
+/// Edit the device config in `$VISUAL` or `$EDITOR`, like `kubectl edit`
+///
+/// The device is only opened to read and to write, so the keyboard works in the editor.
+/// A document that does not parse, or holds illegal values unless `force` is set, is opened
+/// again with the errors on top until it is valid or emptied. Cancelling is not an error.
+pub(crate) fn run(
+    format: ConfigFormat,
+    force: bool,
+    yes: bool,
+    policy: TransferPolicy,
+) -> Result<(), Error> {
+    let current = with_device(policy, read_config)?;
+    let path = temp_dir().join(format!(
+        "gpd-controls-edit-{}.{}",
+        std::process::id(),
+        format.extension()
+    ));
+    let mut body = current.to_commented_string(format)?;
+    let mut errors: Option<String> = None;
+    let config = loop {
+        let header = header(format, errors.as_deref());
+        write(&path, header.clone() + &body).map_err(|e| Error::from(e).context(path.display()))?;
+        open_editor(&path)?;
+        let edited = read_to_string(&path).map_err(|e| Error::from(e).context(path.display()))?;
+        body = strip_header(&header, &edited);
+        if uncommented(format, &body).trim().is_empty() {
+            remove_file(&path).ok();
+            info!("Edit cancelled, the document is empty");
+            return Ok(());
+        }
+        match parse(format, &body, force) {
+            Ok(config) => break config,
+            Err(e) => errors = Some(e),
+        }
+    };
+    remove_file(&path).ok();
+
+    let changes = current.diff(&config);
+    if changes.is_empty() {
+        info!("Edit cancelled, no changes made");
+        return Ok(());
+    }
+    print_changes(&changes);
+    if !yes && !confirm("Apply these changes?")? {
+        info!("Edit cancelled");
+        return Ok(());
+    }
+    with_device(policy, |device| write_and_save(device, config, force))
+}
+
+fn comment(format: ConfigFormat) -> &'static str {
+    match format {
+        ConfigFormat::Json => "//",
+        ConfigFormat::Toml | ConfigFormat::Yaml => "#",
+    }
+}
+
+/// Instructions and the errors of the last attempt, ended by an empty line
+fn header(format: ConfigFormat, errors: Option<&str>) -> String {
+    let mut lines = vec![
+        "Edit the config of the device and save to apply it.".to_string(),
+        format!(
+            "Lines starting with {} are ignored, an empty document cancels the edit.",
+            comment(format)
+        ),
+    ];
+    if let Some(errors) = errors {
+        lines.push(String::new());
+        lines.push("The document is invalid:".to_string());
+        lines.extend(errors.lines().map(|line| format!("  {}", line)));
+    }
+    lines
+        .iter()
+        .map(|line| format!("{} {}\n", comment(format), line).replace(" \n", "\n"))
+        .chain(["\n".to_string()])
+        .collect()
+}
+
+/// Drop the lines of `header` the document still starts with, comments below it are kept
+fn strip_header(header: &str, s: &str) -> String {
+    let matching = header
+        .lines()
+        .zip(s.lines())
+        .take_while(|(written, line)| written.trim_end() == line.trim_end())
+        .count();
+    s.lines()
+        .skip(matching)
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// JSON has no comments, comment lines are blanked before parsing to keep line numbers
+fn uncommented(format: ConfigFormat, s: &str) -> String {
+    s.lines()
+        .map(
+            |line| match line.trim_start().starts_with(comment(format)) {
+                true => "\n".to_string(),
+                false => format!("{}\n", line),
+            },
+        )
+        .collect()
+}
+
+/// Parse and, unless `force` is set, validate the document below the header
+///
+/// The body is parsed behind as many empty lines as the header shown with its errors has, so
+/// line numbers in the errors match the reopened document.
+fn parse(format: ConfigFormat, body: &str, force: bool) -> Result<ControlsConfig, String> {
+    let parse_at = |offset: usize| {
+        let s = "\n".repeat(offset) + &uncommented(format, body);
+        ControlsConfig::from_str_with(&s, format, ParseMode::Strict).map_err(|e| e.to_string())
+    };
+    let config = parse_at(header(format, None).lines().count())
+        .or_else(|e| parse_at(header(format, Some(&e)).lines().count()))?;
+    match config.validate() {
+        Err(violations) if !force => Err(violations
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")),
+        _ => Ok(config),
+    }
+}
+
+/// `$VISUAL`, `$EDITOR` or vi, which may include arguments like `code --wait`
+fn open_editor(path: &Path) -> Result<(), Error> {
+    let editor = var("VISUAL")
+        .or_else(|_| var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| Error::from(e).context(program))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", editor, status).into());
+    }
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<bool, Error> {
+    print!("{} [y/N] ", question);
+    stdout().flush()?;
+    let mut answer = String::new();
+    stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,104 @@
+use serde_json::json;
+
+use gpd_linuxcontrols::error::{Error, ErrorCategory};
+use gpd_linuxcontrols::log::error;
+use gpd_linuxcontrols::protocol::{PRODUCT_ID, VENDOR_ID};
+
+use crate::cli::OutputFormat;
+
+/// Exit code, name and description of each [ErrorCategory]
+trait CategoryInfo {
+    fn exit_code(&self) -> i32;
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+}
+
+const CATEGORIES: [ErrorCategory; 7] = [
+    ErrorCategory::Other,
+    ErrorCategory::NotFound,
+    ErrorCategory::Permission,
+    ErrorCategory::Busy,
+    ErrorCategory::Validation,
+    ErrorCategory::Protocol,
+    ErrorCategory::Io,
+];
+
+impl CategoryInfo for ErrorCategory {
+    /// 2 is left to clap for invalid arguments
+    fn exit_code(&self) -> i32 {
+        match self {
+            ErrorCategory::Other => 1,
+            ErrorCategory::NotFound => 3,
+            ErrorCategory::Permission => 4,
+            ErrorCategory::Busy => 5,
+            ErrorCategory::Validation => 6,
+            ErrorCategory::Protocol => 7,
+            ErrorCategory::Io => 8,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ErrorCategory::Other => "other",
+            ErrorCategory::NotFound => "not-found",
+            ErrorCategory::Permission => "permission",
+            ErrorCategory::Busy => "busy",
+            ErrorCategory::Validation => "validation",
+            ErrorCategory::Protocol => "protocol",
+            ErrorCategory::Io => "io",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ErrorCategory::Other => "any other error",
+            ErrorCategory::NotFound => "device, profile, preset or history entry not found",
+            ErrorCategory::Permission => "no permission to access the device or a file",
+            ErrorCategory::Busy => "device used by another process",
+            ErrorCategory::Validation => "invalid config, field, value or input file",
+            ErrorCategory::Protocol => "failed or unexpected transfer with the device",
+            ErrorCategory::Io => "reading or writing a file or process failed",
+        }
+    }
+}
+
+/// Exit codes for the help text
+pub(crate) fn exit_codes_help() -> String {
+    let mut codes: Vec<(i32, String)> = CATEGORIES
+        .iter()
+        .map(|category| {
+            let text = format!("{}: {}", category.name(), category.description());
+            (category.exit_code(), text)
+        })
+        .collect();
+    codes.push((0, "success".to_string()));
+    codes.push((2, "invalid arguments".to_string()));
+    codes.sort();
+    let lines: Vec<String> = codes
+        .iter()
+        .map(|(code, text)| format!("  {}  {}", code, text))
+        .collect();
+    format!("Exit codes:\n{}", lines.join("\n"))
+}
+
+/// Print `error`, as JSON object on stderr with `--output json`, and return the exit code
+pub(crate) fn report(output: Option<OutputFormat>, error: &Error) -> i32 {
+    let category = error.category;
+    match output {
+        Some(OutputFormat::Json | OutputFormat::JsonCompact) => {
+            let value = json!({
+                "kind": category.name(),
+                "exit_code": category.exit_code(),
+                "message": error.message,
+                "path": error.path,
+                "device": error.device.then(|| json!({
+                    "vendor_id": format!("{:04x}", VENDOR_ID),
+                    "product_id": format!("{:04x}", PRODUCT_ID),
+                })),
+            });
+            eprintln!("{}", value);
+        }
+        _ => error!("{}", error),
+    }
+    category.exit_code()
+}
@@ -0,0 +1,93 @@
+use std::fs::{read_to_string, write};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use gpd_linuxcontrols::controls_field::format::ConfigFormat;
+use gpd_linuxcontrols::controls_field::parse::ParseMode;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::profile::resolve::{resolve, ProfileSource};
+use gpd_linuxcontrols::profile::store::ProfileStore;
+use gpd_linuxcontrols::protocol::function::read_config;
+use gpd_linuxcontrols::protocol::image::PageImage;
+use gpd_linuxcontrols::protocol::Device;
+
+use crate::cheat_sheet;
+use crate::cli::ExportFormat;
+use crate::helper::print_share_code;
+
+impl ExportFormat {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "svg" => Some(ExportFormat::Svg),
+            "html" | "htm" => Some(ExportFormat::Html),
+            _ => ConfigFormat::from_path(path).map(Into::into),
+        }
+    }
+}
+
+impl From<ConfigFormat> for ExportFormat {
+    fn from(value: ConfigFormat) -> Self {
+        match value {
+            ConfigFormat::Json => ExportFormat::Json,
+            ConfigFormat::Toml => ExportFormat::Toml,
+            ConfigFormat::Yaml => ExportFormat::Yaml,
+        }
+    }
+}
+
+/// Export the config of the device, or of `profile` without the device
+pub(crate) fn run(
+    device: Option<&Device>,
+    profile: Option<String>,
+    file: Option<PathBuf>,
+    format: Option<ExportFormat>,
+    code: bool,
+    qr: bool,
+    image: bool,
+) -> Result<(), Error> {
+    let device = || device.ok_or_else(|| Error::from("This command needs the device"));
+    if let (true, Some(path)) = (image, &file) {
+        return write(path, PageImage::read(device()?)?.to_bytes())
+            .map_err(|e| Error::from(e).context(path.display()));
+    }
+    let (title, description, config) = match &profile {
+        Some(name) => {
+            let store = ProfileStore::open_default()?;
+            let config = resolve(&store, name, ParseMode::Strict)?.config;
+            let description = store.load_profile(name, ParseMode::Lenient)?.description;
+            (name.clone(), description, config)
+        }
+        None => ("device".to_string(), None, read_config(device()?)?),
+    };
+    if code {
+        return print_share_code(&config, qr);
+    }
+    let format = format
+        .or_else(|| file.as_deref().and_then(ExportFormat::from_path))
+        .unwrap_or(ExportFormat::Json);
+    let title = format!("GPD controls: {}", title);
+    let content = match format {
+        ExportFormat::Svg => cheat_sheet::svg(&title, description.as_deref(), &config),
+        ExportFormat::Html => cheat_sheet::html(&title, description.as_deref(), &config),
+        ExportFormat::Json | ExportFormat::Toml | ExportFormat::Yaml => {
+            let format = match format {
+                ExportFormat::Toml => ConfigFormat::Toml,
+                ExportFormat::Yaml => ConfigFormat::Yaml,
+                _ => ConfigFormat::Json,
+            };
+            // Keeps the comments of an existing TOML file
+            match file.as_deref().map(read_to_string) {
+                Some(Ok(existing)) => config.update_document(&existing, format)?,
+                Some(Err(e)) if e.kind() != ErrorKind::NotFound => {
+                    return Err(Error::from(e).context(file.unwrap_or_default().display()))
+                }
+                _ => config.to_string_with(format)?,
+            }
+        }
+    };
+    match file {
+        None => println!("{}", content.trim_end()),
+        Some(path) => write(&path, content).map_err(|e| Error::from(e).context(path.display()))?,
+    }
+    Ok(())
+}
@@ -1,8 +1,21 @@
+use std::env::args;
+use std::time::Duration;
+
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+
 use gpd_linuxcontrols::colored::Colorize;
 use gpd_linuxcontrols::controls_field::hid_usage_id_u8::HIDUsageIDu8;
-use gpd_linuxcontrols::log::warn;
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::error::{Error, ErrorCategory};
+use gpd_linuxcontrols::history::{History, Snapshot};
+use gpd_linuxcontrols::log::info;
+use gpd_linuxcontrols::protocol::function::{read_config, save, write_config};
+use gpd_linuxcontrols::protocol::policy::TransferPolicy;
+use gpd_linuxcontrols::protocol::{connect, disconnect, find, Device};
+use gpd_linuxcontrols::rusb::UsbContext;
 
-use crate::cli::HIDUsageIDArgs;
+use crate::cli::{HIDUsageIDArgs, TransferArgs};
 
 impl From<HIDUsageIDArgs> for HIDUsageIDu8 {
     fn from(value: HIDUsageIDArgs) -> Self {
@@ -13,35 +26,82 @@ impl From<HIDUsageIDArgs> for HIDUsageIDu8 {
     }
 }
 
-pub(crate) struct RangeValidator {
-    pub(crate) force: bool,
+impl From<TransferArgs> for TransferPolicy {
+    fn from(value: TransferArgs) -> Self {
+        let default = TransferPolicy::default();
+        TransferPolicy {
+            timeout: value.timeout.map_or(default.timeout, Duration::from_millis),
+            retries: value.retries.unwrap_or(default.retries),
+            command_delay: value
+                .command_delay
+                .map_or(default.command_delay, Duration::from_millis),
+            retry_on_header_mismatch: !value.no_header_retry,
+        }
+    }
 }
 
-impl RangeValidator {
-    pub(crate) fn validate_dead_zones(&self, value: i8, field: &str) -> Result<i8, String> {
-        if (-10i8..=10i8).contains(&value) {
-            return Ok(value);
-        }
-        if self.force {
-            warn!("Dead zones value {} for field DeadZones::{} not in range -10..=10, continue with {}", value, field, "--force".italic());
-            return Ok(value);
-        }
-        Err(format!(
-            "Dead zones value {} for field DeadZones::{} not in range -10..=10, use {} to continue",
-            value,
-            field,
-            "--force".italic()
-        ))
+/// Write and save `config`, unless `force` is set illegal values are rejected by [write_config]
+///
+/// Nothing is written if the device already holds `config`, otherwise the current config is
+/// added to the history first, so the change can be undone.
+pub(crate) fn write_and_save<T: UsbContext>(
+    device: &Device<T>,
+    config: ControlsConfig,
+    force: bool,
+) -> Result<(), Error> {
+    let current = read_config(device)?;
+    if current == config {
+        info!("Device already holds this config, nothing written");
+        return Ok(());
     }
+    snapshot(current)?;
+    write_config(device, config, force.into()).map_err(|e| match e.category {
+        ErrorCategory::Validation => Error {
+            message: format!("{}\n\nuse {} to continue", e.message, "--force".italic()),
+            ..e
+        },
+        _ => e,
+    })?;
+    save(device)
+}
 
-    pub(crate) fn validate_delay(&self, value: u8, field: &str) -> Result<u8, String> {
-        if value == 0 || value == 100 {
-            return Ok(value);
-        }
-        if self.force {
-            warn!("Dead zones value {} for field BackButton::{}Delay not 0(0x00) or 100(0x64), continue with {}", value, field, "--force".italic());
-            return Ok(value);
-        }
-        Err(format!("Dead zones value {} for field BackButton::{}Delay not 0(0x00) or 100(0x64), use {} to continue", value, field, "--force".italic()))
+/// Add `config` to the history, together with the command line about to replace it
+pub(crate) fn snapshot(config: ControlsConfig) -> Result<(), Error> {
+    History::open_default()?.push(&Snapshot::new(
+        config,
+        args().collect::<Vec<String>>().join(" "),
+    ))
+}
+
+/// Open the device only for the duration of `f`
+///
+/// While the device is connected its kernel driver is detached, so keyboard and mouse don't work.
+pub(crate) fn with_device<R>(
+    policy: TransferPolicy,
+    f: impl FnOnce(&Device) -> Result<R, Error>,
+) -> Result<R, Error> {
+    let mut device = find(policy)?;
+    connect(&mut device)?;
+    let result = f(&device);
+    let disconnected = disconnect(device);
+    let value = result?;
+    disconnected.map(|_| value)
+}
+
+/// Print the share code of `config`, with `qr` also as QR code made of half blocks
+pub(crate) fn print_share_code(config: &ControlsConfig, qr: bool) -> Result<(), Error> {
+    let code = config.to_share_code();
+    if qr {
+        let qr_code = QrCode::new(&code).map_err(|e| Error::validation(e.to_string()))?;
+        println!(
+            "{}",
+            qr_code
+                .render::<Dense1x2>()
+                .dark_color(Dense1x2::Light)
+                .light_color(Dense1x2::Dark)
+                .build()
+        );
     }
+    println!("{}", code);
+    Ok(())
 }
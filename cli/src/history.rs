@@ -0,0 +1,39 @@
+use gpd_linuxcontrols::controls_field::validation::ValidationPolicy;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::history::History;
+use gpd_linuxcontrols::protocol::function::{save, write_config};
+use gpd_linuxcontrols::protocol::Device;
+
+use crate::cli::HistoryCommand;
+
+pub(crate) fn run(command: HistoryCommand) -> Result<(), Error> {
+    let history = History::open_default()?;
+    match command {
+        HistoryCommand::List => history
+            .list()?
+            .iter()
+            .enumerate()
+            .for_each(|(i, snapshot)| {
+                println!("{:<6}{:24}{}", i + 1, snapshot.created, snapshot.command)
+            }),
+        HistoryCommand::Show { n, format } => {
+            println!("{}", history.get(n)?.to_string_with(format)?)
+        }
+    }
+    Ok(())
+}
+
+/// Write back the newest snapshot and drop it, so repeated undos go further back
+pub(crate) fn undo(device: &Device) -> Result<(), Error> {
+    let history = History::open_default()?;
+    let snapshot = history.get(1)?;
+    // The snapshot holds what the device held, even values the validation would reject
+    write_config(device, snapshot.config, ValidationPolicy::Force)?;
+    save(device)?;
+    history.remove(1)?;
+    println!(
+        "Restored config saved at {} before: {}",
+        snapshot.created, snapshot.command
+    );
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use gpd_linuxcontrols::controls_field::registry::*;
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::enums::hid_usage_id::HIDUsageID;
+
+const GAP: usize = 6;
+
+/// Part of a line of the diagram, showing the value of `field` if set
+#[derive(Clone)]
+pub(crate) struct Segment {
+    pub(crate) text: String,
+    pub(crate) field: Option<&'static Field>,
+}
+
+impl Segment {
+    fn text(text: impl Into<String>) -> Self {
+        Segment {
+            text: text.into(),
+            field: None,
+        }
+    }
+
+    fn field(text: String, field: &'static Field) -> Self {
+        Segment {
+            text,
+            field: Some(field),
+        }
+    }
+}
+
+type Line = Vec<Segment>;
+
+/// Keys and delays of a back button, in the order they are pressed
+pub(crate) struct Sequence {
+    pub(crate) label: &'static str,
+    pub(crate) keys: [&'static Field; 4],
+    pub(crate) delays: [&'static Field; 3],
+}
+
+pub(crate) const BACK_BUTTONS: [Sequence; 2] = [
+    Sequence {
+        label: "L4",
+        keys: [
+            &BACK_BUTTON_LEFT_FIRST,
+            &BACK_BUTTON_LEFT_SECOND,
+            &BACK_BUTTON_LEFT_THIRD,
+            &BACK_BUTTON_LEFT_FORTH,
+        ],
+        delays: [
+            &BACK_BUTTON_LEFT_FIRST_DELAY,
+            &BACK_BUTTON_LEFT_SECOND_DELAY,
+            &BACK_BUTTON_LEFT_THIRD_DELAY,
+        ],
+    },
+    Sequence {
+        label: "R4",
+        keys: [
+            &BACK_BUTTON_RIGHT_FIRST,
+            &BACK_BUTTON_RIGHT_SECOND,
+            &BACK_BUTTON_RIGHT_THIRD,
+            &BACK_BUTTON_RIGHT_FORTH,
+        ],
+        delays: [
+            &BACK_BUTTON_RIGHT_FIRST_DELAY,
+            &BACK_BUTTON_RIGHT_SECOND_DELAY,
+            &BACK_BUTTON_RIGHT_THIRD_DELAY,
+        ],
+    },
+];
+
+impl Sequence {
+    /// Number of keys up to the last assigned one, at least the first key
+    pub(crate) fn used(&self, config: &ControlsConfig) -> usize {
+        let unassigned = FieldValue::Key(HIDUsageID::NoEvent.into());
+        self.keys
+            .iter()
+            .rposition(|key| key.get(config) != unassigned)
+            .map_or(1, |last| last + 1)
+    }
+}
+
+/// Draw the controls of the handheld with their assigned keys, back buttons on top
+pub(crate) fn render(config: &ControlsConfig) -> String {
+    diagram(config)
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|segment| segment.text.as_str())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Lines of [render], every key and delay as its own segment
+pub(crate) fn diagram(config: &ControlsConfig) -> Vec<Line> {
+    let mut lines: Vec<Line> = BACK_BUTTONS
+        .iter()
+        .map(|button| sequence(config, button))
+        .collect();
+    lines.push(Vec::new());
+    lines.extend(side_by_side(
+        cross(
+            config,
+            "D-pad",
+            [&DPAD_UP, &DPAD_DOWN, &DPAD_LEFT, &DPAD_RIGHT],
+            None,
+        ),
+        cross(
+            config,
+            "ABXY",
+            [&Y_BUTTON, &A_BUTTON, &X_BUTTON, &B_BUTTON],
+            None,
+        ),
+    ));
+    lines.push(Vec::new());
+    lines.extend(side_by_side(
+        cross(
+            config,
+            "Left stick",
+            [
+                &LEFT_STICK_UP,
+                &LEFT_STICK_DOWN,
+                &LEFT_STICK_LEFT,
+                &LEFT_STICK_RIGHT,
+            ],
+            Some(&LEFT_STICK_PUSH),
+        ),
+        push(config, "Right stick", &RIGHT_STICK_PUSH),
+    ));
+    lines
+}
+
+fn name(config: &ControlsConfig, field: &Field) -> String {
+    field.get(config).to_string()
+}
+
+fn width(line: &Line) -> usize {
+    line.iter()
+        .map(|segment| segment.text.chars().count())
+        .sum()
+}
+
+/// Keys pressed one after another, trailing unassigned keys are left out
+fn sequence(config: &ControlsConfig, button: &Sequence) -> Line {
+    let mut line = vec![Segment::text(format!("{}  ", button.label))];
+    (0..button.used(config)).for_each(|i| {
+        if i > 0 {
+            let delay = button.delays[i - 1];
+            line.push(Segment::text(" -"));
+            line.push(Segment::field(name(config, delay), delay));
+            line.push(Segment::text("-> "));
+        }
+        let key = button.keys[i];
+        line.push(Segment::field(format!("[{}]", name(config, key)), key));
+    });
+    line
+}
+
+/// Up, down, left and right around an optional center, each column as wide as its keys
+fn cross(
+    config: &ControlsConfig,
+    title: &str,
+    [up, down, left, right]: [&'static Field; 4],
+    center: Option<&'static Field>,
+) -> Vec<Line> {
+    let width = [up, down]
+        .into_iter()
+        .chain(center)
+        .map(|field| name(config, field).len())
+        .max()
+        .unwrap_or(0);
+    let cell =
+        |field: &'static Field| Segment::field(format!("[{:^width$}]", name(config, field)), field);
+    let left_cell = Segment::field(format!("[{}]", name(config, left)), left);
+    let right_cell = Segment::field(format!("[{}]", name(config, right)), right);
+    let total = left_cell.text.len() + width + 2 + right_cell.text.len();
+    let indent = " ".repeat(left_cell.text.len());
+    let middle = center.map_or_else(|| Segment::text(" ".repeat(width + 2)), cell);
+    vec![
+        vec![Segment::text(format!("{:^total$}", title))],
+        vec![Segment::text(indent.clone()), cell(up)],
+        vec![left_cell, middle, right_cell],
+        vec![Segment::text(indent), cell(down)],
+    ]
+}
+
+/// A single key, aligned with the middle row of a [cross]
+fn push(config: &ControlsConfig, title: &str, field: &'static Field) -> Vec<Line> {
+    let cell = Segment::field(format!("[{}]", name(config, field)), field);
+    vec![
+        vec![Segment::text(format!(
+            "{:^total$}",
+            title,
+            total = cell.text.len()
+        ))],
+        Vec::new(),
+        vec![cell],
+    ]
+}
+
+fn side_by_side(left: Vec<Line>, right: Vec<Line>) -> Vec<Line> {
+    let column = left.iter().map(width).max().unwrap_or(0);
+    (0..left.len().max(right.len()))
+        .map(|i| {
+            let mut line = left.get(i).cloned().unwrap_or_default();
+            line.push(Segment::text(" ".repeat(column - width(&line) + GAP)));
+            line.extend(right.get(i).cloned().unwrap_or_default());
+            line
+        })
+        .collect()
+}
@@ -1,39 +1,81 @@
-use std::fmt::Display;
-use std::fs::create_dir_all;
+use std::fs::{create_dir_all, read, read_to_string, write};
 use std::io::Read;
 use std::process::exit;
 
+use clap::error::ErrorKind;
 use clap::{CommandFactory, Parser};
 
+use gpd_linuxcontrols::backup::Backup;
 use gpd_linuxcontrols::controls_field::back_button::BackButtonConfig;
 use gpd_linuxcontrols::controls_field::dead_zones::DeadZonesConfig;
+use gpd_linuxcontrols::controls_field::format::ConfigFormat;
 use gpd_linuxcontrols::controls_field::keyboard_mouse::KeyboardMouseConfig;
+use gpd_linuxcontrols::controls_field::parse::ParseMode;
+use gpd_linuxcontrols::controls_field::registry::{FieldValue, FIELDS, VIBRATE};
 use gpd_linuxcontrols::controls_field::ControlsConfig;
-use gpd_linuxcontrols::enums::{BackButton, DeadZone};
-use gpd_linuxcontrols::log::{debug, error};
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::log::{debug, error, warn};
 use gpd_linuxcontrols::protocol::function::{
-    read_all, read_checksum, read_config, read_firmware_version, save, write_config,
+    check_firmware, read_all, read_checksum, read_config, read_firmware_version, save,
 };
-use gpd_linuxcontrols::protocol::raw::{get_report, set_report};
+use gpd_linuxcontrols::protocol::image::PageImage;
+use gpd_linuxcontrols::protocol::policy::TransferPolicy;
+use gpd_linuxcontrols::protocol::raw::{get_report_partial, set_report, REPORT_LENGTH};
 use gpd_linuxcontrols::protocol::{connect, detach_kernel_driver, disconnect, find};
 use gpd_linuxcontrols::strum::IntoEnumIterator;
-use gpd_linuxcontrols::LoadArray;
 
 use crate::cli::{
-    Cli, Commands, GenCommand, KernelDriverCommand, RawCommand, ReadCommand, ResetCommand,
-    WriteCommand,
+    Cli, Commands, GenCommand, KernelDriverCommand, KeyboardMouseFormat, OutputFormat, RawCommand,
+    ReadCommand, ResetCommand, WriteCommand,
 };
-use crate::helper::RangeValidator;
 
+use crate::error::report;
+use crate::helper::{snapshot, write_and_save};
+use crate::tui::Target;
+
+mod batch;
+mod cheat_sheet;
 mod cli;
+mod daemon;
+mod diff;
+mod edit;
+mod error;
+mod export;
 mod helper;
+mod history;
+mod layout;
+mod output;
+mod preset;
+mod profile;
+mod run;
+mod tui;
 
 fn main() {
     let args = cli::Cli::parse();
+    // `conflicts_with` misses a global --output given before the subcommand
+    if let (
+        Commands::Read {
+            read_command: ReadCommand::KeyboardMouse { format: Some(_) },
+        },
+        Some(_),
+    ) = (&args.command, args.output)
+    {
+        Cli::command()
+            .bin_name("gpd-controls")
+            .error(
+                ErrorKind::ArgumentConflict,
+                "the argument '--format <FORMAT>' cannot be used with '--output <OUTPUT>'",
+            )
+            .exit();
+    }
     let log_level = args.verbose.log_level_filter();
     env_logger::Builder::new().filter_level(log_level).init();
-    gpd_linuxcontrols::protocol::set_logger(log_level);
     debug!("{args:?}");
+    let policy: TransferPolicy = args.transfer.into();
+    let output = args.output;
+    if let Some(OutputFormat::Json | OutputFormat::JsonCompact) = output {
+        gpd_linuxcontrols::colored::control::set_override(false);
+    }
 
     match args.command {
         Commands::HIDUsageID => {
@@ -47,49 +89,151 @@ fn main() {
             exit(0);
         }
         Commands::Gen { gen_command, path } => {
-            (|| -> Result<(), String> {
+            (|| -> Result<(), Error> {
                 let out_dir = path.to_path_buf();
                 let cmd = Cli::command();
                 debug!("man: generate to{:?}", out_dir);
-                create_dir_all(&out_dir).map_err(|e| e.to_string())?;
+                create_dir_all(&out_dir)?;
 
                 match gen_command {
                     GenCommand::Man => {
-                        clap_mangen::generate_to(Cli::command(), out_dir)
-                            .map_err(|e| e.to_string())?;
+                        clap_mangen::generate_to(Cli::command(), out_dir)?;
                     }
                     GenCommand::Complete { args } => {
                         let name = cmd.get_display_name().unwrap_or_else(|| cmd.get_name());
-                        clap_complete::generate_to(args, &mut Cli::command(), name, &out_dir)
-                            .err()
-                            .inspect(|e| {
-                                error!("{}", e);
-                                exit(1);
-                            });
+                        clap_complete::generate_to(args, &mut Cli::command(), name, &out_dir)?;
                     }
                 }
                 Ok(())
             })()
             .err()
             .inspect(|e| {
-                error!("{}", e);
-                exit(1);
+                exit(report(output, e));
+            });
+            exit(0);
+        }
+        Commands::Profile { profile_command } if !profile_command.needs_device() => {
+            profile::run(profile_command, None).err().inspect(|e| {
+                exit(report(output, e));
+            });
+            exit(0);
+        }
+        Commands::Daemon { rules, dry_run } => {
+            if !dry_run {
+                gpd_linuxcontrols::protocol::set_logger(log_level);
+            }
+            daemon::run(rules, dry_run, policy).err().inspect(|e| {
+                exit(report(output, e));
+            });
+            exit(0);
+        }
+        Commands::Run {
+            profile,
+            save,
+            force,
+            command,
+        } => {
+            gpd_linuxcontrols::protocol::set_logger(log_level);
+            exit(
+                run::run(profile, save, force, command, policy)
+                    .unwrap_or_else(|e| report(output, &e)),
+            );
+        }
+        Commands::Diff { a, b, json } if !a.needs_device() && !b.needs_device() => {
+            diff::run(a, b, json, None).err().inspect(|e| {
+                exit(report(output, e));
+            });
+            exit(0);
+        }
+        Commands::Import {
+            code,
+            dry_run: true,
+            ..
+        } => {
+            ControlsConfig::from_share_code(&code)
+                .map(|config| println!("{}", config))
+                .err()
+                .inspect(|e| {
+                    exit(report(output, e));
+                });
+            exit(0);
+        }
+        Commands::Export {
+            file,
+            format,
+            profile: Some(profile),
+            code,
+            qr,
+            image,
+        } => {
+            export::run(None, Some(profile), file, format, code, qr, image)
+                .err()
+                .inspect(|e| {
+                    exit(report(output, e));
+                });
+            exit(0);
+        }
+        Commands::Fields => {
+            FIELDS.iter().for_each(|field| {
+                println!(
+                    "{:48}{:40}{}",
+                    field.path,
+                    field.kind.allowed(),
+                    field.description
+                )
+            });
+            exit(0);
+        }
+        Commands::History { history_command } => {
+            history::run(history_command).err().inspect(|e| {
+                exit(report(output, e));
+            });
+            exit(0);
+        }
+        Commands::Edit { format, force, yes } => {
+            gpd_linuxcontrols::protocol::set_logger(log_level);
+            edit::run(format, force, yes, policy).err().inspect(|e| {
+                exit(report(output, e));
+            });
+            exit(0);
+        }
+        Commands::Tui {
+            file,
+            profile,
+            force,
+        } => {
+            let target = match (file, profile) {
+                (Some(path), _) => Target::File(path),
+                (_, Some(name)) => Target::Profile(name),
+                _ => {
+                    gpd_linuxcontrols::protocol::set_logger(log_level);
+                    Target::Device { force, policy }
+                }
+            };
+            tui::run(target).err().inspect(|e| {
+                exit(report(output, e));
+            });
+            exit(0);
+        }
+        Commands::Preset { preset_command } if !preset_command.needs_device() => {
+            preset::run(preset_command, None).err().inspect(|e| {
+                exit(report(output, e));
             });
             exit(0);
         }
         _ => {}
     }
 
-    let mut device = find().unwrap_or_else(|error| {
-        error!("{}", error);
-        exit(1);
+    gpd_linuxcontrols::protocol::set_logger(log_level);
+    let mut device = find(policy).unwrap_or_else(|error| {
+        exit(report(output, &error));
     });
 
     if let Commands::KernelDriver {
         kernel_driver_command,
     } = args.command
     {
-        (|| -> Result<(), String> {
+        (|| -> Result<(), Error> {
             match kernel_driver_command {
                 KernelDriverCommand::Detach => detach_kernel_driver(device),
                 KernelDriverCommand::Attach => {
@@ -101,206 +245,190 @@ fn main() {
         })()
         .map_or_else(
             |e| {
-                error!("{}", e);
-                exit(1);
+                exit(report(output, &e));
             },
             |_| exit(0),
         );
     } else {
-        let code = (|| -> Result<(), String> {
+        let code = (|| -> Result<(), Error> {
             connect(&mut device)?;
             match args.command {
-                Commands::Read { read_command } => (|| -> Result<String, String> {
-                    let result: Result<Box<dyn Display>, String> = match read_command {
-                        ReadCommand::Firmware => read_firmware_version(&device)
-                            .map(|v| -> Box<dyn Display> { Box::new(v) }),
-                        ReadCommand::Checksum => {
-                            read_checksum(&device).map(|v| -> Box<dyn Display> { Box::new(v) })
+                Commands::Read { read_command } => (|| -> Result<String, Error> {
+                    let (root, value) = match read_command {
+                        ReadCommand::Firmware => (
+                            Some("firmware_version"),
+                            serde_json::to_value(read_firmware_version(&device)?),
+                        ),
+                        ReadCommand::Checksum => (
+                            Some("checksum"),
+                            serde_json::to_value(read_checksum(&device)?),
+                        ),
+                        ReadCommand::KeyboardMouse {
+                            format: Some(KeyboardMouseFormat::Layout),
+                        } => {
+                            return Ok(layout::render(&read_config(&device)?));
                         }
                         other => {
                             let config = read_all(&device)?;
-                            let result: Box<dyn Display> = match other {
-                                ReadCommand::All => Box::new(config),
-                                ReadCommand::Config => Box::<ControlsConfig>::new(config.into()),
-                                ReadCommand::KeyboardMouse => Box::new(config.keyboard_mouse),
-                                ReadCommand::BackButton => Box::new(config.back_button),
-                                ReadCommand::Vibrate => Box::new(config.vibrate),
-                                ReadCommand::DeadZones => Box::new(config.dead_zones),
-                                ReadCommand::BackButtonDelay => Box::new(config.back_button),
+                            match other {
+                                ReadCommand::All => (None, serde_json::to_value(config)),
+                                ReadCommand::Config => {
+                                    (None, serde_json::to_value(ControlsConfig::from(config)))
+                                }
+                                ReadCommand::KeyboardMouse { .. } => (
+                                    Some("keyboard_mouse"),
+                                    serde_json::to_value(config.keyboard_mouse),
+                                ),
+                                ReadCommand::BackButton | ReadCommand::BackButtonDelay => (
+                                    Some("back_button"),
+                                    serde_json::to_value(config.back_button),
+                                ),
+                                ReadCommand::Vibrate => {
+                                    (Some(VIBRATE.path), serde_json::to_value(config.vibrate))
+                                }
+                                ReadCommand::DeadZones => {
+                                    (Some("dead_zones"), serde_json::to_value(config.dead_zones))
+                                }
                                 _ => panic!("Never reach!"),
-                            };
-                            Ok(result)
+                            }
                         }
                     };
-                    result.map(|v| v.to_string())
+                    output
+                        .unwrap_or(OutputFormat::Json)
+                        .render(root, value.map_err(|e| Error::validation(e.to_string()))?)
                 })()
                 .map(|v| {
                     println!("{}", v);
                 }),
+                Commands::Write {
+                    write_command: WriteCommand::Image { file },
+                    force,
+                } => {
+                    let content =
+                        read(&file).map_err(|e| Error::from(e).context(file.display()))?;
+                    let image =
+                        PageImage::from_bytes(&content).map_err(|e| e.context(file.display()))?;
+                    check_firmware(&device, &image.firmware_version, force)?;
+                    snapshot(read_config(&device)?)?;
+                    image.write_to(&device)?;
+                    save(&device)
+                }
                 Commands::Write {
                     write_command,
                     force,
                 } => {
                     let mut config = read_config(&device)?;
-                    let validator = RangeValidator { force };
                     match write_command {
-                        WriteCommand::Config { mut file } => {
+                        WriteCommand::Config {
+                            mut file,
+                            format,
+                            lenient,
+                        } => {
+                            let format = ConfigFormat::resolve(format, file.path());
                             let mut str: String = Default::default();
-                            file.read_to_string(&mut str).map_err(|e| e.to_string())?;
+                            file.read_to_string(&mut str)?;
                             debug!("read: {}", str);
-                            config = serde_json::from_str(&str)
-                                .map_err(|e| -> String { e.to_string() })?;
+                            config = ControlsConfig::from_str_with(&str, format, lenient.into())?;
                             debug!("deserialized: {}", config);
-                            validator.validate_dead_zones(
-                                config.dead_zones.left.border,
-                                "Left::Border",
-                            )?;
-                            validator.validate_dead_zones(
-                                config.dead_zones.left.center,
-                                "Left::Border",
-                            )?;
-                            validator.validate_dead_zones(
-                                config.dead_zones.right.border,
-                                "Right::Border",
-                            )?;
-                            validator.validate_dead_zones(
-                                config.dead_zones.right.center,
-                                "Right::Border",
-                            )?;
-
-                            validator.validate_delay(
-                                config.back_button.left.first_delay,
-                                "Left::First",
-                            )?;
-                            validator.validate_delay(
-                                config.back_button.left.second_delay,
-                                "Left::Second",
-                            )?;
-                            validator.validate_delay(
-                                config.back_button.left.third_delay,
-                                "Left::Third",
-                            )?;
-                            validator.validate_delay(
-                                config.back_button.right.first_delay,
-                                "Right::First",
-                            )?;
-                            validator.validate_delay(
-                                config.back_button.right.second_delay,
-                                "Right::Second",
-                            )?;
-                            validator.validate_delay(
-                                config.back_button.right.third_delay,
-                                "Right::Third",
-                            )?;
                         }
-                        WriteCommand::KeyboardMouse(args) => match args.key {
-                            gpd_linuxcontrols::enums::KeyboardMouse::DPadUp => {
-                                config.keyboard_mouse.directional_pad.up = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::DPadDown => {
-                                config.keyboard_mouse.directional_pad.down = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::DPadLeft => {
-                                config.keyboard_mouse.directional_pad.left = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::DPadRight => {
-                                config.keyboard_mouse.directional_pad.right = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::AButton => {
-                                config.keyboard_mouse.a = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::BButton => {
-                                config.keyboard_mouse.b = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::XButton => {
-                                config.keyboard_mouse.x = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::YButton => {
-                                config.keyboard_mouse.y = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::LeftStickUp => {
-                                config.keyboard_mouse.left_stick.up = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::LeftStickDown => {
-                                config.keyboard_mouse.left_stick.down = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::LeftStickLeft => {
-                                config.keyboard_mouse.left_stick.left = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::LeftStickRight => {
-                                config.keyboard_mouse.left_stick.right = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::LeftStickPush => {
-                                config.keyboard_mouse.left_stick.push = args.value.into()
-                            }
-                            gpd_linuxcontrols::enums::KeyboardMouse::RightStickPush => {
-                                config.keyboard_mouse.right_stick_push = args.value.into()
-                            }
-                        },
-                        WriteCommand::BackButton(args) => match args.key {
-                            BackButton::Left1 => config.back_button.left.first = args.value.into(),
-                            BackButton::Left2 => config.back_button.left.second = args.value.into(),
-                            BackButton::Left3 => config.back_button.left.third = args.value.into(),
-                            BackButton::Left4 => config.back_button.left.forth = args.value.into(),
-                            BackButton::Right1 => {
-                                config.back_button.right.first = args.value.into()
-                            }
-                            BackButton::Right2 => {
-                                config.back_button.right.second = args.value.into()
-                            }
-                            BackButton::Right3 => {
-                                config.back_button.right.third = args.value.into()
-                            }
-                            BackButton::Right4 => {
-                                config.back_button.right.forth = args.value.into()
-                            }
-                        },
-                        WriteCommand::Vibrate(args) => config.vibrate = args.value,
-                        WriteCommand::DeadZone(args) => match args.key {
-                            DeadZone::LeftCenter => {
-                                config.dead_zones.left.center =
-                                    validator.validate_dead_zones(args.value, "Left::Center")?
-                            }
-                            DeadZone::LeftBorder => {
-                                config.dead_zones.left.border =
-                                    validator.validate_dead_zones(args.value, "Left::Border")?
-                            }
-                            DeadZone::RightCenter => {
-                                config.dead_zones.right.center =
-                                    validator.validate_dead_zones(args.value, "Right::Center")?
-                            }
-                            DeadZone::RightBorder => {
-                                config.dead_zones.right.border =
-                                    validator.validate_dead_zones(args.value, "Right::Border")?
-                            }
-                        },
-                        WriteCommand::BackButtonDelay(args) => {
-                            match args.key {
-                                gpd_linuxcontrols::enums::BackButtonDelay::Left1 => {
-                                    validator.validate_delay(args.value, "Left::First")?
-                                }
-                                gpd_linuxcontrols::enums::BackButtonDelay::Left2 => {
-                                    validator.validate_delay(args.value, "Left::Second")?
-                                }
-                                gpd_linuxcontrols::enums::BackButtonDelay::Left3 => {
-                                    validator.validate_delay(args.value, "Left::Third")?
-                                }
-                                gpd_linuxcontrols::enums::BackButtonDelay::Right1 => {
-                                    validator.validate_delay(args.value, "Right::First")?
-                                }
-                                gpd_linuxcontrols::enums::BackButtonDelay::Right2 => {
-                                    validator.validate_delay(args.value, "Right::Second")?
-                                }
-                                gpd_linuxcontrols::enums::BackButtonDelay::Right3 => {
-                                    validator.validate_delay(args.value, "Right::Third")?
-                                }
-                            };
+                        WriteCommand::KeyboardMouse(args) => args
+                            .key
+                            .field()
+                            .set(&mut config, FieldValue::Key(args.value.into()))?,
+                        WriteCommand::BackButton(args) => args
+                            .key
+                            .field()
+                            .set(&mut config, FieldValue::Key(args.value.into()))?,
+                        WriteCommand::Vibrate(args) => {
+                            VIBRATE.set(&mut config, FieldValue::Vibrate(args.value))?
                         }
+                        WriteCommand::DeadZone(args) => args
+                            .key
+                            .field()
+                            .set(&mut config, FieldValue::DeadZone(args.value))?,
+                        WriteCommand::BackButtonDelay(args) => args
+                            .key
+                            .field()
+                            .set(&mut config, FieldValue::Delay(args.value))?,
+                        WriteCommand::Image { .. } => panic!("Never reach!"),
+                    }
+                    write_and_save(&device, config, force)
+                }
+                Commands::Export {
+                    file,
+                    format,
+                    profile,
+                    code,
+                    qr,
+                    image,
+                } => export::run(Some(&device), profile, file, format, code, qr, image),
+                Commands::Backup { file, format } => {
+                    let content = Backup::read(&device)?
+                        .to_string_with(ConfigFormat::resolve(format, &file))?;
+                    write(&file, content).map_err(|e| Error::from(e).context(file.display()))
+                }
+                Commands::Restore {
+                    file,
+                    format,
+                    force,
+                } => {
+                    let content = read_to_string(&file)
+                        .map_err(|e| Error::from(e).context(file.display()))?;
+                    let backup = Backup::from_str_with(
+                        &content,
+                        ConfigFormat::resolve(format, &file),
+                        ParseMode::Strict,
+                    )
+                    .map_err(|e| e.context(file.display()))?;
+                    backup.check_firmware(&device, force)?;
+                    snapshot(read_config(&device)?)?;
+                    backup.write_to(&device)
+                }
+                Commands::Undo => history::undo(&device),
+                Commands::Get { path } => {
+                    let config = read_config(&device)?;
+                    match path {
+                        Some(path) => println!("{}", config.get(&path)?),
+                        None => FIELDS
+                            .iter()
+                            .for_each(|field| println!("{:48}{}", field.path, field.get(&config))),
                     }
-                    write_config(&device, config)?;
-                    save(&device)?;
                     Ok(())
                 }
+                Commands::Set {
+                    assignments,
+                    force,
+                    dry_run,
+                } => batch::run(&device, batch::from_args(assignments)?, force, dry_run),
+                Commands::Batch {
+                    file,
+                    force,
+                    dry_run,
+                } => batch::run(&device, batch::from_input(file)?, force, dry_run),
+                Commands::Import { code, force, .. } => {
+                    write_and_save(&device, ControlsConfig::from_share_code(&code)?, force)
+                }
+                Commands::Profile { profile_command } => {
+                    profile::run(profile_command, Some(&device))
+                }
+                Commands::Preset { preset_command } => preset::run(preset_command, Some(&device)),
+                Commands::Diff { a, b, json } => diff::run(a, b, json, Some(&device)),
+                Commands::Patch {
+                    file,
+                    lenient,
+                    force,
+                    dry_run,
+                } => {
+                    let patch: serde_json::Value = serde_json::from_reader(file)
+                        .map_err(|e| Error::validation(e.to_string()))?;
+                    debug!("patch: {}", patch);
+                    let config = read_config(&device)?.apply_patch(&patch, lenient.into())?;
+                    if dry_run {
+                        println!("{}", config);
+                        return Ok(());
+                    }
+                    write_and_save(&device, config, force)
+                }
                 Commands::Reset { reset_command } => {
                     let mut config = read_config(&device)?;
                     match reset_command {
@@ -317,21 +445,30 @@ fn main() {
                             config = ControlsConfig::default();
                         }
                     }
-                    write_config(&device, config)?;
-                    save(&device)?;
-                    Ok(())
+                    // Reset must succeed even if the device holds illegal values in other fields
+                    write_and_save(&device, config, true)
                 }
                 Commands::Raw { raw_command } => match raw_command {
                     RawCommand::SetReport { data } => {
                         let mut load: [u8; 33] = [0; 33];
                         hex::decode_to_slice(data.trim_start_matches("0x"), &mut load as &mut [u8])
-                            .map_err(|e| e.to_string())?;
-                        set_report(&device, load).map_err(|e| e.to_string())?;
+                            .map_err(|e| Error::validation(e.to_string()))?;
+                        set_report(&device, load)?;
                         Ok(())
                     }
                     RawCommand::GetReport => {
-                        get_report(&device).map(|v| {
-                            println!("{:#X}", <[u8; 65] as Into<LoadArray<65>>>::into(v));
+                        get_report_partial(&device).map(|v| {
+                            if v.len() < REPORT_LENGTH {
+                                warn!(
+                                    "Short read, received {} of {} bytes",
+                                    v.len(),
+                                    REPORT_LENGTH
+                                );
+                            }
+                            println!(
+                                "{}",
+                                v.iter().map(|i| format!("{:02X} ", i)).collect::<String>()
+                            );
                         })?;
                         Ok(())
                     }
@@ -339,13 +476,7 @@ fn main() {
                 _ => panic!("should not reach!"),
             }
         })()
-        .map_or_else(
-            |e| -> i32 {
-                error!("{}", e);
-                1
-            },
-            |_| 0,
-        );
+        .map_or_else(|e| report(output, &e), |_| 0);
 
         disconnect(device).err().inspect(|e| error!("{}", e));
         exit(code);
@@ -0,0 +1,99 @@
+use serde_json::{json, Value};
+
+use gpd_linuxcontrols::controls_field::format::ConfigFormat;
+use gpd_linuxcontrols::error::Error;
+
+use crate::cli::OutputFormat;
+
+impl OutputFormat {
+    /// Render any serialized value, e.g. a section of the config read from the device
+    ///
+    /// `root` is the path of `value` inside the whole config, e.g. `keyboard_mouse`. `value` is
+    /// nested under it in every format, so keys and paths match those of `get` and `set`.
+    pub(crate) fn render(&self, root: Option<&str>, value: Value) -> Result<String, Error> {
+        let value = match root {
+            Some(root) => json!({ root: value }),
+            None => value,
+        };
+        match self {
+            OutputFormat::Json => {
+                serde_json::to_string_pretty(&value).map_err(|e| Error::from(e.to_string()))
+            }
+            OutputFormat::JsonCompact => {
+                serde_json::to_string(&value).map_err(|e| Error::from(e.to_string()))
+            }
+            OutputFormat::Yaml => ConfigFormat::Yaml
+                .serialize(&value)
+                .map(|s| s.trim_end().to_string()),
+            OutputFormat::Toml => ConfigFormat::Toml
+                .serialize(&value)
+                .map(|s| s.trim_end().to_string()),
+            OutputFormat::Table => {
+                let rows = flatten(&value);
+                let width = rows.iter().map(|(path, _)| path.len()).max().unwrap_or(0);
+                Ok(rows
+                    .iter()
+                    .map(|(path, value)| format!("{:width$}  {}", path, text(value)))
+                    .collect::<Vec<String>>()
+                    .join("\n"))
+            }
+            OutputFormat::Env => Ok(flatten(&value)
+                .iter()
+                .map(|(path, value)| {
+                    format!(
+                        "{}={}",
+                        path.to_uppercase().replace(['.', '-'], "_"),
+                        shell_quote(&text(value))
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")),
+        }
+    }
+}
+
+/// Dotted path and value of every leaf, array elements are addressed by index
+fn flatten(value: &Value) -> Vec<(String, Value)> {
+    fn visit(path: String, value: &Value, rows: &mut Vec<(String, Value)>) {
+        let join = |key: &str| match path.is_empty() {
+            true => key.to_string(),
+            false => format!("{}.{}", path, key),
+        };
+        match value {
+            Value::Object(map) => map
+                .iter()
+                .for_each(|(key, value)| visit(join(key), value, rows)),
+            Value::Array(values) => values
+                .iter()
+                .enumerate()
+                .for_each(|(i, value)| visit(join(&i.to_string()), value, rows)),
+            leaf => rows.push((path, leaf.clone())),
+        }
+    }
+    let mut rows = Vec::new();
+    let path = match value {
+        Value::Object(_) | Value::Array(_) => String::new(),
+        _ => "value".to_string(),
+    };
+    visit(path, value, &mut rows);
+    rows
+}
+
+/// Strings without quotes, everything else as JSON
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.:/+,".contains(c))
+    {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+}
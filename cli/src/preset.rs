@@ -0,0 +1,42 @@
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::profile::preset::{find, Preset, PRESETS};
+use gpd_linuxcontrols::protocol::Device;
+
+use crate::cli::PresetCommand;
+use crate::helper::write_and_save;
+
+impl PresetCommand {
+    pub(crate) fn needs_device(&self) -> bool {
+        matches!(self, PresetCommand::Apply { .. })
+    }
+}
+
+pub(crate) fn run(command: PresetCommand, device: Option<&Device>) -> Result<(), Error> {
+    match command {
+        PresetCommand::Apply { name, force } => {
+            let device = device.ok_or_else(|| "This command needs the device".to_string())?;
+            write_and_save(device, lookup(&name)?.config(), force)?;
+        }
+        PresetCommand::List => PRESETS
+            .iter()
+            .for_each(|preset| println!("{:32}{}", preset.name, preset.description)),
+        PresetCommand::Show { name, format } => {
+            println!("{}", lookup(&name)?.profile().to_string_with(format)?);
+        }
+    }
+    Ok(())
+}
+
+fn lookup(name: &str) -> Result<&'static Preset, Error> {
+    find(name).ok_or_else(|| {
+        Error::not_found(format!(
+            "Preset {} not found, available: {}",
+            name,
+            PRESETS
+                .iter()
+                .map(|preset| preset.name)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ))
+    })
+}
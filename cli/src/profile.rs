@@ -0,0 +1,103 @@
+use gpd_linuxcontrols::controls_field::parse::ParseMode;
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::profile::layer::ConfigLayer;
+use gpd_linuxcontrols::profile::resolve::resolve;
+use gpd_linuxcontrols::profile::store::ProfileStore;
+use gpd_linuxcontrols::profile::Profile;
+use gpd_linuxcontrols::protocol::function::{read_config, read_firmware_version};
+use gpd_linuxcontrols::protocol::Device;
+
+use crate::cli::ProfileCommand;
+use crate::helper::write_and_save;
+
+impl ProfileCommand {
+    pub(crate) fn needs_device(&self) -> bool {
+        matches!(
+            self,
+            ProfileCommand::Save { .. } | ProfileCommand::Apply { .. }
+        )
+    }
+}
+
+pub(crate) fn run(command: ProfileCommand, device: Option<&Device>) -> Result<(), Error> {
+    let store = ProfileStore::open_default()?;
+    let device = || device.ok_or_else(|| Error::from("This command needs the device"));
+    match command {
+        ProfileCommand::Save {
+            name,
+            description,
+            extends,
+            format,
+            overwrite,
+        } => {
+            let device = device()?;
+            let config = read_config(device)?;
+            let mut profile =
+                Profile::new(config, description, Some(read_firmware_version(device)?));
+            if let Some(base) = extends {
+                let base_config = resolve(&store, &base, ParseMode::Strict)?.config;
+                profile.config = ConfigLayer::difference(&config, &base_config);
+                profile.extends = Some(base);
+            }
+            let path = store.save(&name, &profile, format, overwrite)?;
+            println!("Saved profile {} to {}", name, path.display());
+        }
+        ProfileCommand::Apply { name, force } => {
+            let config = resolve(&store, &name, ParseMode::Strict)?.config;
+            write_and_save(device()?, config, force)?;
+        }
+        ProfileCommand::List => store.list()?.iter().for_each(|name| {
+            let description = store
+                .load(name, ParseMode::Lenient)
+                .map(|profile| profile.description.unwrap_or_default())
+                .unwrap_or_else(|e| e.to_string());
+            println!("{:32}{}", name, description);
+        }),
+        ProfileCommand::Show {
+            name,
+            resolved: true,
+            ..
+        } => {
+            let resolved = resolve(&store, &name, ParseMode::Strict)?;
+            println!("# {}", resolved.chain.join(" -> "));
+            fields(&resolved.config)?.iter().for_each(|(path, value)| {
+                println!(
+                    "{:48}{:32}{}",
+                    path,
+                    value,
+                    resolved.source(path).unwrap_or("default")
+                )
+            });
+        }
+        ProfileCommand::Show { name, format, .. } => {
+            println!(
+                "{}",
+                store
+                    .load(&name, ParseMode::Strict)?
+                    .to_string_with(format)?
+            );
+        }
+        ProfileCommand::Rm { name } => store.remove(&name)?,
+        ProfileCommand::Rename { from, to } => store.rename(&from, &to)?,
+    }
+    Ok(())
+}
+
+/// Dotted path and JSON value of every field of `config`
+fn fields(config: &ControlsConfig) -> Result<Vec<(String, String)>, Error> {
+    let layer = ConfigLayer::full(config);
+    let value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    Ok(layer
+        .paths()
+        .into_iter()
+        .map(|path| {
+            let field = path
+                .split('.')
+                .try_fold(&value, |value, key| value.get(key))
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+            (path, field)
+        })
+        .collect())
+}
@@ -0,0 +1,146 @@
+use std::os::unix::process::ExitStatusExt;
+use std::process::Command;
+use std::thread::spawn;
+
+use gpd_linuxcontrols::controls_field::parse::ParseMode;
+use gpd_linuxcontrols::controls_field::validation::ValidationPolicy;
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::log::{debug, error, info, warn};
+use gpd_linuxcontrols::profile::resolve::resolve;
+use gpd_linuxcontrols::profile::store::ProfileStore;
+use gpd_linuxcontrols::protocol::function::{read_config, save, write_config};
+use gpd_linuxcontrols::protocol::policy::TransferPolicy;
+use signal_hook::consts::{SIGHUP, SIGINT, SIGQUIT, SIGTERM, SIGUSR1, SIGUSR2};
+use signal_hook::iterator::exfiltrator::WithOrigin;
+use signal_hook::iterator::SignalsInfo;
+use signal_hook::low_level::siginfo::Cause;
+
+use crate::helper::{snapshot, with_device};
+
+const FORWARDED_SIGNALS: [i32; 6] = [SIGHUP, SIGINT, SIGQUIT, SIGTERM, SIGUSR1, SIGUSR2];
+
+/// Apply `profile`, run `command` and restore the previous config, returns the exit code to use
+///
+/// Unless `save` is set the ROM is never written, so even if this process is killed by SIGKILL
+/// the previous config comes back once the device is power cycled.
+pub(crate) fn run(
+    profile: String,
+    save: bool,
+    force: bool,
+    command: Vec<String>,
+    policy: TransferPolicy,
+) -> Result<i32, Error> {
+    let store = ProfileStore::open_default()?;
+    let config = resolve(&store, &profile, ParseMode::Strict)?.config;
+
+    // Handlers are registered before applying, a SIGTERM in between must not skip the restore
+    let signals = SignalsInfo::<WithOrigin>::new(FORWARDED_SIGNALS)?;
+    let snapshot = with_device(policy, |device| {
+        let snapshot = read_config(device)?;
+        if snapshot != config {
+            // The ROM holds the profile until the restore, undo must be able to bring it back
+            if save {
+                self::snapshot(snapshot)?;
+            }
+            write_config(device, config, force.into())?;
+            if save {
+                self::save(device)?;
+            }
+        }
+        Ok(snapshot)
+    })?;
+    info!("Applied profile {}", profile);
+    let guard = Restore {
+        snapshot,
+        applied: config,
+        save,
+        policy,
+        done: false,
+    };
+
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .spawn()
+        .and_then(|mut child| {
+            let pid = child.id() as libc::pid_t;
+            let handle = signals.handle();
+            let forward = spawn(move || forward(signals, pid));
+            let status = child.wait();
+            handle.close();
+            forward.join().ok();
+            status
+        })
+        .map_err(|e| Error::from(e).context(&command[0]));
+
+    guard.restore()?;
+    let status = status?;
+    debug!("{}: {}", command[0], status);
+    Ok(status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(1))
+}
+
+/// Pass every signal sent to this process on to the child
+///
+/// Signals generated by the terminal, e.g. Ctrl-C, already reached the whole foreground process
+/// group including the child, those are not sent a second time.
+fn forward(mut signals: SignalsInfo<WithOrigin>, pid: libc::pid_t) {
+    signals
+        .forever()
+        .filter(|origin| origin.cause != Cause::Kernel)
+        .for_each(|origin| {
+            debug!("Forward signal {} to {}", origin.signal, pid);
+            // SAFETY: kill has no memory safety requirements
+            if unsafe { libc::kill(pid, origin.signal) } != 0 {
+                warn!(
+                    "Forward signal {}: {}",
+                    origin.signal,
+                    std::io::Error::last_os_error()
+                );
+            }
+        });
+}
+
+/// Restores the config read before applying the profile, when dropped without [Restore::restore]
+/// too, so a panic does not leave the profile applied
+struct Restore {
+    snapshot: ControlsConfig,
+    applied: ControlsConfig,
+    save: bool,
+    policy: TransferPolicy,
+    done: bool,
+}
+
+impl Restore {
+    fn restore(mut self) -> Result<(), Error> {
+        self.done = true;
+        self.write()
+    }
+
+    fn write(&self) -> Result<(), Error> {
+        if self.snapshot == self.applied {
+            return Ok(());
+        }
+        with_device(self.policy, |device| {
+            // The config read before may hold illegal values, it is restored as it was
+            write_config(device, self.snapshot, ValidationPolicy::Force)?;
+            if self.save {
+                save(device)?;
+            }
+            Ok(())
+        })
+        .map_err(|e| e.context("Restore previous config"))?;
+        info!("Restored previous config");
+        Ok(())
+    }
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if !self.done {
+            self.write().err().inspect(|e| error!("{}", e));
+        }
+    }
+}
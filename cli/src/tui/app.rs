@@ -0,0 +1,231 @@
+use clap::ValueEnum;
+use ratatui::crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::DefaultTerminal;
+
+use gpd_linuxcontrols::controls_field::hid_usage_id_u8::HIDUsageIDu8;
+use gpd_linuxcontrols::controls_field::registry::{Field, FieldKind, FieldValue, FIELDS};
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::enums::hid_usage_id::HIDUsageID;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::strum::IntoEnumIterator;
+
+use crate::tui::{ui, Target};
+
+pub(super) enum Mode {
+    Browse,
+    /// Searchable list of HID usages for a key field
+    PickKey {
+        search: String,
+        selected: usize,
+    },
+    /// Free text for every other field, checked on enter
+    Input {
+        text: String,
+        error: Option<String>,
+    },
+    Diff,
+    Message(String),
+}
+
+pub(super) struct App {
+    pub(super) target: Target,
+    /// Config as last loaded or written
+    pub(super) original: ControlsConfig,
+    pub(super) config: ControlsConfig,
+    pub(super) selected: usize,
+    pub(super) mode: Mode,
+    pub(super) status: String,
+    quit_armed: bool,
+    quit: bool,
+}
+
+impl App {
+    pub(super) fn new(target: Target, config: ControlsConfig) -> Self {
+        App {
+            status: format!("Loaded {}", target.describe()),
+            target,
+            original: config,
+            config,
+            selected: 0,
+            mode: Mode::Browse,
+            quit_armed: false,
+            quit: false,
+        }
+    }
+
+    pub(super) fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), Error> {
+        while !self.quit {
+            terminal.draw(|frame| ui::draw(frame, self))?;
+            if let Event::Key(key) = read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn field(&self) -> &'static Field {
+        FIELDS[self.selected]
+    }
+
+    pub(super) fn is_modified(&self) -> bool {
+        self.original != self.config
+    }
+
+    /// HID usages whose name or id contains `search`, exact and prefix matches first
+    pub(super) fn candidates(search: &str) -> Vec<(String, HIDUsageIDu8)> {
+        let search = search.to_lowercase();
+        let mut candidates: Vec<(String, HIDUsageIDu8)> = HIDUsageID::iter()
+            .filter_map(|usage| {
+                let name = usage.to_possible_value()?.get_name().to_string();
+                let id: u8 = usage.into();
+                (name.contains(&search) || format!("{:#04x}", id).contains(&search))
+                    .then(|| (name, id.into()))
+            })
+            .collect();
+        candidates.sort_by_key(|(name, _)| (*name != search, !name.starts_with(&search)));
+        candidates
+    }
+
+    fn handle(&mut self, key: KeyEvent) {
+        let mode = std::mem::replace(&mut self.mode, Mode::Browse);
+        self.mode = match mode {
+            Mode::Browse => return self.browse(key.code),
+            Mode::PickKey { search, selected } => self.pick_key(key.code, search, selected),
+            Mode::Input { text, error } => self.input(key.code, text, error),
+            Mode::Diff | Mode::Message(_) => Mode::Browse,
+        }
+    }
+
+    fn browse(&mut self, code: KeyCode) {
+        if !matches!(code, KeyCode::Char('q') | KeyCode::Esc) {
+            self.quit_armed = false;
+        }
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(FIELDS.len() - 1)
+            }
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = FIELDS.len() - 1,
+            KeyCode::Enter => self.mode = self.editor(),
+            KeyCode::Char('x') if self.field().kind == FieldKind::Key => {
+                self.mode = self.set(FieldValue::Key(HIDUsageID::NoEvent.into()))
+            }
+            KeyCode::Char('u') => self.mode = self.set(self.field().get(&self.original)),
+            KeyCode::Char('d') => self.mode = Mode::Diff,
+            KeyCode::Char('w') => self.write(),
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if self.is_modified() && !self.quit_armed {
+                    self.quit_armed = true;
+                    self.status = "Unsaved changes, press q again to discard them".to_string();
+                } else {
+                    self.quit = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn editor(&self) -> Mode {
+        let value = self.field().get(&self.config);
+        match value {
+            FieldValue::Key(_) => {
+                let selected = Self::candidates("")
+                    .iter()
+                    .position(|(_, id)| FieldValue::Key(*id) == value)
+                    .unwrap_or_default();
+                Mode::PickKey {
+                    search: String::new(),
+                    selected,
+                }
+            }
+            _ => Mode::Input {
+                text: value.to_string(),
+                error: None,
+            },
+        }
+    }
+
+    fn pick_key(&mut self, code: KeyCode, mut search: String, selected: usize) -> Mode {
+        let candidates = Self::candidates(&search);
+        match code {
+            KeyCode::Esc => return Mode::Browse,
+            KeyCode::Enter => {
+                return match candidates.get(selected) {
+                    Some((_, id)) => self.set(FieldValue::Key(*id)),
+                    None => Mode::Browse,
+                }
+            }
+            KeyCode::Up => {
+                return Mode::PickKey {
+                    search,
+                    selected: selected.saturating_sub(1),
+                }
+            }
+            KeyCode::Down => {
+                return Mode::PickKey {
+                    search,
+                    selected: (selected + 1).min(candidates.len().saturating_sub(1)),
+                }
+            }
+            KeyCode::Backspace => {
+                search.pop();
+            }
+            KeyCode::Char(c) => search.push(c),
+            _ => {}
+        }
+        Mode::PickKey {
+            search,
+            selected: 0,
+        }
+    }
+
+    fn input(&mut self, code: KeyCode, mut text: String, error: Option<String>) -> Mode {
+        match code {
+            KeyCode::Esc => return Mode::Browse,
+            KeyCode::Enter => match self.field().parse(&text) {
+                Ok(value) => return self.set(value),
+                Err(e) => {
+                    return Mode::Input {
+                        text,
+                        error: Some(e.to_string()),
+                    }
+                }
+            },
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Char(c) => text.push(c),
+            _ => return Mode::Input { text, error },
+        }
+        Mode::Input { text, error: None }
+    }
+
+    /// Set the selected field, returns the mode showing the error if it failed
+    fn set(&mut self, value: FieldValue) -> Mode {
+        let field = self.field();
+        match field.set(&mut self.config, value) {
+            Ok(()) => {
+                self.status = format!("{} = {}", field.path, value);
+                Mode::Browse
+            }
+            Err(e) => Mode::Message(e.to_string()),
+        }
+    }
+
+    fn write(&mut self) {
+        if !self.is_modified() {
+            self.status = "Nothing to write".to_string();
+            return;
+        }
+        match self.target.save(self.config) {
+            Ok(()) => {
+                self.original = self.config;
+                self.status = format!("Written to {}", self.target.describe());
+            }
+            Err(e) => self.mode = Mode::Message(e.to_string()),
+        }
+    }
+}
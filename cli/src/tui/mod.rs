@@ -0,0 +1,103 @@
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+
+use gpd_linuxcontrols::controls_field::format::ConfigFormat;
+use gpd_linuxcontrols::controls_field::parse::ParseMode;
+use gpd_linuxcontrols::controls_field::ControlsConfig;
+use gpd_linuxcontrols::error::Error;
+use gpd_linuxcontrols::log::{max_level, set_max_level, LevelFilter};
+use gpd_linuxcontrols::profile::layer::ConfigLayer;
+use gpd_linuxcontrols::profile::resolve::{resolve, ProfileSource};
+use gpd_linuxcontrols::profile::store::ProfileStore;
+use gpd_linuxcontrols::protocol::function::read_config;
+use gpd_linuxcontrols::protocol::policy::TransferPolicy;
+
+use crate::helper::{with_device, write_and_save};
+use crate::tui::app::App;
+
+mod app;
+mod ui;
+
+/// Where the edited config comes from and is written to
+pub(crate) enum Target {
+    /// Opened only to read and to write, keyboard and mouse keep working in between
+    Device {
+        force: bool,
+        policy: TransferPolicy,
+    },
+    File(PathBuf),
+    Profile(String),
+}
+
+impl Target {
+    fn describe(&self) -> String {
+        match self {
+            Target::Device { .. } => "device".to_string(),
+            Target::File(path) => format!("file {}", path.display()),
+            Target::Profile(name) => format!("profile {}", name),
+        }
+    }
+
+    fn load(&self) -> Result<ControlsConfig, Error> {
+        match self {
+            Target::Device { policy, .. } => with_device(*policy, read_config),
+            Target::File(path) => {
+                let s = read_to_string(path).map_err(|e| Error::from(e).context(path.display()))?;
+                ControlsConfig::from_str_with(
+                    &s,
+                    ConfigFormat::resolve(None, path),
+                    ParseMode::Strict,
+                )
+                .map_err(|e| e.context(path.display()))
+            }
+            Target::Profile(name) => {
+                Ok(resolve(&ProfileStore::open_default()?, name, ParseMode::Strict)?.config)
+            }
+        }
+    }
+
+    fn save(&self, config: ControlsConfig) -> Result<(), Error> {
+        match self {
+            Target::Device { force, policy } => {
+                with_device(*policy, |device| write_and_save(device, config, *force))
+            }
+            Target::File(path) => {
+                let format = ConfigFormat::resolve(None, path);
+                // Keeps the comments of a TOML file
+                let content = match read_to_string(path) {
+                    Ok(existing) => config.update_document(&existing, format)?,
+                    Err(_) => config.to_string_with(format)?,
+                };
+                write(path, content).map_err(|e| Error::from(e).context(path.display()))
+            }
+            Target::Profile(name) => {
+                let store = ProfileStore::open_default()?;
+                let mut profile = store.load_profile(name, ParseMode::Strict)?;
+                // A profile extending another one keeps storing only its overrides
+                profile.config = match &profile.extends {
+                    Some(base) => ConfigLayer::difference(
+                        &config,
+                        &resolve(&store, base, ParseMode::Strict)?.config,
+                    ),
+                    None => ConfigLayer::full(&config),
+                };
+                let format = store.path(name).map_or(ConfigFormat::Toml, |path| {
+                    ConfigFormat::resolve(None, &path)
+                });
+                store.save(name, &profile, format, true).map(|_| ())
+            }
+        }
+    }
+}
+
+/// Logging is turned off while the terminal is in the alternate screen, log lines would tear it
+pub(crate) fn run(target: Target) -> Result<(), Error> {
+    let config = target.load()?;
+    let mut terminal = ratatui::try_init()?;
+    let level = max_level();
+    set_max_level(LevelFilter::Off);
+    let result = App::new(target, config).run(&mut terminal);
+    ratatui::restore();
+    set_max_level(level);
+    result
+}
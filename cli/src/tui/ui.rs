@@ -0,0 +1,186 @@
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::Frame;
+
+use gpd_linuxcontrols::controls_field::registry::FIELDS;
+
+use crate::layout;
+use crate::tui::app::{App, Mode};
+
+const HELP: &str = "↑/↓ select  enter edit  x clear key  u revert  d diff  w write  q quit";
+
+pub(super) fn draw(frame: &mut Frame, app: &App) {
+    let [title, body, help, status] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let mut heading = vec![Span::from(format!(" gpd-controls: {}", app.target.describe())).bold()];
+    if app.is_modified() {
+        heading.push(Span::from(" [modified]").yellow());
+    }
+    frame.render_widget(Line::from(heading), title);
+    let height = layout::diagram(&app.config).len() as u16 + 2;
+    let [diagram, fields] =
+        Layout::vertical([Constraint::Length(height), Constraint::Min(3)]).areas(body);
+    draw_diagram(frame, app, diagram);
+    draw_fields(frame, app, fields);
+    frame.render_widget(Line::from(HELP).dim(), help);
+    frame.render_widget(Line::from(format!(" {}", app.status)), status);
+
+    match &app.mode {
+        Mode::Browse => {}
+        Mode::PickKey { search, selected } => draw_key_picker(frame, app, search, *selected),
+        Mode::Input { text, error } => draw_input(frame, app, text, error.as_deref()),
+        Mode::Diff => draw_diff(frame, app),
+        Mode::Message(message) => draw_message(frame, message),
+    }
+}
+
+/// The controls as in `read keyboard-mouse --format layout`, the selected field highlighted
+fn draw_diagram(frame: &mut Frame, app: &App, area: Rect) {
+    let lines: Vec<Line> = layout::diagram(&app.config)
+        .into_iter()
+        .map(|line| {
+            Line::from(
+                line.into_iter()
+                    .map(|segment| match segment.field {
+                        Some(field) if std::ptr::eq(field, app.field()) => {
+                            Span::from(segment.text).reversed()
+                        }
+                        Some(field) if field.get(&app.config) != field.get(&app.original) => {
+                            Span::from(segment.text).yellow().bold()
+                        }
+                        _ => Span::from(segment.text),
+                    })
+                    .collect::<Vec<Span>>(),
+            )
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered()), area);
+}
+
+fn draw_fields(frame: &mut Frame, app: &App, area: Rect) {
+    let width = FIELDS
+        .iter()
+        .map(|field| field.path.len())
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let items: Vec<ListItem> = FIELDS
+        .iter()
+        .map(|field| {
+            let value = field.get(&app.config);
+            let original = field.get(&app.original);
+            let mut spans = vec![Span::from(format!("{:width$}", field.path))];
+            if value == original {
+                spans.push(Span::from(value.to_string()));
+            } else {
+                spans.push(Span::from(value.to_string()).yellow().bold());
+                spans.push(Span::from(format!("  (was {})", original)).dim());
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::bordered().title(format!(" {} ", app.field().description)))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_key_picker(frame: &mut Frame, app: &App, search: &str, selected: usize) {
+    let area = popup(frame.area(), 50, 70);
+    frame.render_widget(Clear, area);
+    let [input, list] = Layout::vertical([Constraint::Length(3), Constraint::Min(1)]).areas(area);
+    frame.render_widget(
+        Paragraph::new(search).block(Block::bordered().title(format!(" {} ", app.field().path))),
+        input,
+    );
+    let items: Vec<ListItem> = App::candidates(search)
+        .into_iter()
+        .map(|(name, id)| ListItem::new(format!("{:#04x}  {}", u8::from(id), name)))
+        .collect();
+    let list_widget = List::new(items)
+        .block(Block::bordered().title(" type to search, enter select, esc cancel "))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list_widget, list, &mut state);
+}
+
+fn draw_input(frame: &mut Frame, app: &App, text: &str, error: Option<&str>) {
+    let area = popup(frame.area(), 60, 30);
+    frame.render_widget(Clear, area);
+    let field = app.field();
+    let mut lines = vec![
+        Line::from(format!("> {}", text)),
+        Line::from(format!("Allowed: {}", field.kind.allowed())).dim(),
+    ];
+    if let Some(error) = error {
+        lines.push(Line::from(error.to_string()).fg(Color::Red));
+    }
+    frame.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(format!(" {} ", field.path))),
+        area,
+    );
+}
+
+fn draw_diff(frame: &mut Frame, app: &App) {
+    let area = popup(frame.area(), 80, 70);
+    frame.render_widget(Clear, area);
+    let changes = app.original.diff(&app.config);
+    let lines: Vec<Line> = if changes.is_empty() {
+        vec![Line::from("No changes")]
+    } else {
+        changes
+            .iter()
+            .map(|change| {
+                Line::from(vec![
+                    Span::from(format!("{}: ", change.path)),
+                    Span::from(change.old.to_string()).red(),
+                    Span::from(" -> "),
+                    Span::from(change.new.to_string()).green(),
+                ])
+            })
+            .collect()
+    };
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title(" Pending changes ")),
+        area,
+    );
+}
+
+fn draw_message(frame: &mut Frame, message: &str) {
+    let area = popup(frame.area(), 60, 30);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(message)
+            .wrap(Wrap { trim: false })
+            .block(Block::bordered().title(" Error ").red()),
+        area,
+    );
+}
+
+/// Centered rect of `width` and `height` percent of `area`
+fn popup(area: Rect, width: u16, height: u16) -> Rect {
+    let [_, vertical, _] = Layout::vertical([
+        Constraint::Percentage((100 - height) / 2),
+        Constraint::Percentage(height),
+        Constraint::Percentage((100 - height) / 2),
+    ])
+    .areas(area);
+    let [_, horizontal, _] = Layout::horizontal([
+        Constraint::Percentage((100 - width) / 2),
+        Constraint::Percentage(width),
+        Constraint::Percentage((100 - width) / 2),
+    ])
+    .areas(vertical);
+    horizontal
+}
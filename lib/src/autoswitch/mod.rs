@@ -0,0 +1,178 @@
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
+
+use crate::autoswitch::process::ProcessInfo;
+use crate::controls_field::format::ConfigFormat;
+use crate::controls_field::parse::ParseMode;
+use crate::error::Error;
+
+pub mod process;
+
+/// Which profile to apply while a matching process runs
+///
+/// Every condition given must match the same process, a rule needs at least one condition.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rule {
+    /// Name of a stored profile or a preset
+    pub profile: String,
+    /// File name of the executable, e.g. `gzdoom` or `Game.exe`
+    pub exe: Option<String>,
+    /// Regular expression searched in the space separated command line
+    pub cmdline: Option<String>,
+    /// Steam app id, e.g. `570`
+    pub steam_app_id: Option<u32>,
+}
+
+/// Rules file of the auto switch daemon
+///
+/// ```toml
+/// default = "desktop"
+/// interval = "2s"
+///
+/// [[rule]]
+/// profile = "wasd"
+/// steam_app_id = 570
+///
+/// [[rule]]
+/// profile = "emulator-hotkeys"
+/// exe = "retroarch"
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter)]
+#[serde(default)]
+pub struct Rules {
+    /// Profile applied while no rule matches, the config found at start if not set
+    pub default: Option<String>,
+    /// How often running processes are scanned
+    #[serde(with = "humantime_duration")]
+    pub interval: Duration,
+    /// Checked in order, the first rule matching any process wins
+    #[serde(rename = "rule")]
+    pub rules: Vec<Rule>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            default: None,
+            interval: Duration::from_secs(2),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Rules {
+    /// `$XDG_CONFIG_HOME/gpd-controls/autoswitch.toml`
+    pub fn default_path() -> Result<PathBuf, Error> {
+        dirs::config_dir()
+            .map(|dir| dir.join("gpd-controls").join("autoswitch.toml"))
+            .ok_or_else(|| Error::from("Could not determine the user config directory"))
+    }
+
+    /// Load a rules file in any [ConfigFormat], TOML unless the extension says otherwise
+    pub fn load(path: &Path, mode: ParseMode) -> Result<Self, Error> {
+        let s = read_to_string(path).map_err(|e| Error::from(e).context(path.display()))?;
+        ConfigFormat::from_path(path)
+            .unwrap_or(ConfigFormat::Toml)
+            .deserialize::<Rules>(&s, mode)
+            .and_then(|rules| rules.check().map(|_| rules))
+            .map_err(|e| e.context(path.display()))
+    }
+
+    /// Reject rules without a profile or without conditions
+    pub fn check(&self) -> Result<(), Error> {
+        self.rules.iter().enumerate().try_for_each(|(i, rule)| {
+            if rule.profile.is_empty() {
+                return Err(Error::validation(format!(
+                    "rule {}: needs a profile",
+                    i + 1
+                )));
+            }
+            if rule.exe.is_none() && rule.cmdline.is_none() && rule.steam_app_id.is_none() {
+                return Err(Error::validation(format!(
+                    "rule {} ({}): needs at least one of exe, cmdline or steam_app_id",
+                    i + 1,
+                    rule.profile
+                )));
+            }
+            Ok(())
+        })
+    }
+
+    /// Compile the rules, rejecting invalid rules and regular expressions
+    pub fn matcher(&self) -> Result<Matcher, Error> {
+        self.check()?;
+        self.rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                let cmdline = rule
+                    .cmdline
+                    .as_deref()
+                    .map(Regex::new)
+                    .transpose()
+                    .map_err(|e| {
+                        Error::validation(format!("rule {} ({}): {}", i + 1, rule.profile, e))
+                    })?;
+                Ok((rule.clone(), cmdline))
+            })
+            .collect::<Result<Vec<_>, Error>>()
+            .map(|rules| Matcher { rules })
+    }
+}
+
+/// Compiled [Rules]
+pub struct Matcher {
+    rules: Vec<(Rule, Option<Regex>)>,
+}
+
+impl Matcher {
+    /// The first rule matched by any of `processes`
+    pub fn select(&self, processes: &[ProcessInfo]) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .find(|(rule, cmdline)| {
+                processes
+                    .iter()
+                    .any(|process| matches(rule, cmdline.as_ref(), process))
+            })
+            .map(|(rule, _)| rule)
+    }
+}
+
+fn matches(rule: &Rule, cmdline: Option<&Regex>, process: &ProcessInfo) -> bool {
+    rule.exe
+        .as_deref()
+        .is_none_or(|exe| process.names().any(|name| name == exe))
+        && cmdline.is_none_or(|regex| regex.is_match(&process.cmdline()))
+        && rule
+            .steam_app_id
+            .is_none_or(|id| process.steam_app_id == Some(id))
+}
+
+mod humantime_duration {
+    use std::time::Duration;
+
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&humantime::format_duration(*value).to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        humantime::parse_duration(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs::{read, read_dir, read_link};
+use std::path::Path;
+
+use crate::error::Error;
+
+/// What is known about a running process, taken from `/proc/<pid>`
+///
+/// Files of processes owned by other users may not be readable, such fields are left empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// Target of `/proc/<pid>/exe`
+    pub exe: Option<String>,
+    /// Arguments from `/proc/<pid>/cmdline`, starting with `argv[0]`
+    pub args: Vec<String>,
+    /// `SteamAppId` from `/proc/<pid>/environ`, set by Steam for every game it launches
+    pub steam_app_id: Option<u32>,
+}
+
+impl ProcessInfo {
+    /// Read process `pid`, `None` if it exited or is a kernel thread
+    pub fn read(pid: u32) -> Option<Self> {
+        Self::read_from(pid, &Path::new("/proc").join(pid.to_string()))
+    }
+
+    fn read_from(pid: u32, dir: &Path) -> Option<Self> {
+        let args: Vec<String> = split_nul(&read(dir.join("cmdline")).ok()?)
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        if args.is_empty() {
+            return None;
+        }
+        let exe = read_link(dir.join("exe")).ok().map(|path| {
+            let path = path.to_string_lossy();
+            path.strip_suffix(" (deleted)").unwrap_or(&path).to_string()
+        });
+        let steam_app_id = read(dir.join("environ")).ok().and_then(|environ| {
+            split_nul(&environ)
+                .filter_map(|v| v.strip_prefix(b"SteamAppId="))
+                .find_map(|v| std::str::from_utf8(v).ok()?.parse().ok())
+        });
+        Some(ProcessInfo {
+            pid,
+            exe,
+            args,
+            steam_app_id,
+        })
+    }
+
+    /// File names the process is known by: of its executable and of `argv[0]`
+    ///
+    /// Windows paths are split too, so a game run by Wine is also known by its `.exe` name.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.exe
+            .iter()
+            .chain(self.args.first())
+            .filter_map(|path| path.rsplit(['/', '\\']).next())
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Arguments joined by spaces
+    pub fn cmdline(&self) -> String {
+        self.args.join(" ")
+    }
+}
+
+/// All user space processes currently running
+pub fn processes() -> Result<Vec<ProcessInfo>, Error> {
+    let mut processes: Vec<ProcessInfo> = read_dir("/proc")
+        .map_err(|e| Error::from(e).context("/proc"))?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(ProcessInfo::read)
+        .collect();
+    processes.sort_by_key(|process| process.pid);
+    Ok(processes)
+}
+
+fn split_nul(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    bytes.split(|b| *b == 0).filter(|v| !v.is_empty())
+}
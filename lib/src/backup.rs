@@ -0,0 +1,112 @@
+use std::time::SystemTime;
+
+use rusb::UsbContext;
+use serde::{Deserialize, Serialize};
+
+use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
+
+use crate::controls_field::format::ConfigFormat;
+use crate::controls_field::parse::ParseMode;
+use crate::controls_field::validation::ValidationPolicy;
+use crate::controls_field::{ControlsConfig, ControlsField};
+use crate::error::Error;
+use crate::protocol::function::{check_firmware, read_all, read_config_pages, save, write_config};
+use crate::protocol::image::PageImage;
+use crate::protocol::Device;
+use crate::LoadArray;
+
+/// Everything readable from the device at one point in time
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter)]
+#[serde(default)]
+pub struct Backup {
+    /// RFC 3339 timestamp of when the backup was taken
+    pub created: String,
+    pub controls: ControlsField,
+    /// Raw responses of the config reads as hex, including bytes the config doesn't decode
+    pub pages: Vec<String>,
+}
+
+impl Backup {
+    pub fn read<T: UsbContext>(device: &Device<T>) -> Result<Self, Error> {
+        Ok(Backup {
+            created: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            controls: read_all(device)?,
+            pages: read_config_pages(device)?
+                .into_iter()
+                .map(|page| {
+                    format!("{:X}", LoadArray::from(page))
+                        .trim_end()
+                        .to_string()
+                })
+                .collect(),
+        })
+    }
+
+    /// Check the firmware, then write the pages back and save them
+    pub fn restore<T: UsbContext>(&self, device: &Device<T>, force: bool) -> Result<(), Error> {
+        self.check_firmware(device, force)?;
+        self.write_to(device)
+    }
+
+    /// A device running other firmware than the one the backup was taken from may interpret
+    /// the fields differently, this is an error unless `force` is set
+    pub fn check_firmware<T: UsbContext>(
+        &self,
+        device: &Device<T>,
+        force: bool,
+    ) -> Result<(), Error> {
+        check_firmware(device, &self.controls.firmware_version, force)
+    }
+
+    /// The stored pages as write commands, see [PageImage::from_read_pages]
+    pub fn page_image(&self) -> Result<PageImage, Error> {
+        let read_pages = self
+            .pages
+            .iter()
+            .enumerate()
+            .map(|(i, page)| {
+                parse_page(page).map_err(|e| Error::validation(format!("pages[{}]: {}", i, e)))
+            })
+            .collect::<Result<Vec<[u8; 65]>, Error>>()?;
+        PageImage::from_read_pages(self.controls.firmware_version, &read_pages)
+    }
+
+    /// Write the stored pages back and save them, without checking the firmware
+    ///
+    /// Bytes no known field covers are restored too, except bytes 16 to 24 of every write page,
+    /// those are never read back, and the pages [PageImage::write_to] leaves untouched.
+    /// A backup without pages writes the decoded config instead.
+    pub fn write_to<T: UsbContext>(&self, device: &Device<T>) -> Result<(), Error> {
+        if self.pages.is_empty() {
+            // The backup holds what the device held, even values the validation would reject
+            write_config(
+                device,
+                ControlsConfig::from(self.controls),
+                ValidationPolicy::Force,
+            )?;
+        } else {
+            self.page_image()?.write_to(device)?;
+        }
+        save(device)
+    }
+
+    pub fn from_str_with(s: &str, format: ConfigFormat, mode: ParseMode) -> Result<Self, Error> {
+        format.deserialize(s, mode)
+    }
+
+    pub fn to_string_with(&self, format: ConfigFormat) -> Result<String, Error> {
+        format.serialize(self)
+    }
+}
+
+/// Bytes of a page stored as space separated hex, like `{:X}` of [LoadArray] writes it
+fn parse_page(s: &str) -> Result<[u8; 65], String> {
+    let bytes = s
+        .split_whitespace()
+        .map(|byte| u8::from_str_radix(byte, 16).map_err(|e| format!("{}: {}", byte, e)))
+        .collect::<Result<Vec<u8>, String>>()?;
+    let length = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| format!("expected 65 bytes, got {}", length))
+}
@@ -1,11 +1,25 @@
-use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
 
 use crate::controls_field::hid_usage_id_u8::HIDUsageIDu8;
+use crate::parse_hex;
 
 #[derive(
-    Debug, Copy, Clone, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
 )]
 #[serde(default)]
 pub struct BackButtonConfig {
@@ -14,7 +28,15 @@ pub struct BackButtonConfig {
 }
 
 #[derive(
-    Debug, Copy, Clone, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
 )]
 #[serde(default)]
 pub struct SpecificBackButtonConfig {
@@ -22,7 +44,167 @@ pub struct SpecificBackButtonConfig {
     pub second: HIDUsageIDu8,
     pub third: HIDUsageIDu8,
     pub forth: HIDUsageIDu8,
-    pub first_delay: u8,
-    pub second_delay: u8,
-    pub third_delay: u8,
+    pub first_delay: BackButtonDelay,
+    pub second_delay: BackButtonDelay,
+    pub third_delay: BackButtonDelay,
+}
+
+/// Delay between two keys of a back button sequence
+///
+/// The firmware stores the delay in milliseconds, but only `0ms` and `100ms` are known to work.
+/// Other bytes can only be created with [BackButtonDelay::from_raw], or `"raw:<byte>"` as string.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BackButtonDelay {
+    raw: u8,
+}
+
+impl BackButtonDelay {
+    pub const NONE: BackButtonDelay = BackButtonDelay { raw: 0 };
+    pub const MS_100: BackButtonDelay = BackButtonDelay { raw: 100 };
+    pub const SUPPORTED: [BackButtonDelay; 2] = [Self::NONE, Self::MS_100];
+
+    /// Accept only delays supported by the firmware
+    pub fn from_duration(duration: Duration) -> Result<Self, String> {
+        Self::SUPPORTED
+            .into_iter()
+            .find(|v| v.duration() == duration)
+            .ok_or_else(|| {
+                format!(
+                    "delay {}ms is not one of {}",
+                    duration.as_millis(),
+                    Self::SUPPORTED
+                        .iter()
+                        .map(|v| v.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )
+            })
+    }
+
+    /// Escape hatch for bytes not known to be supported by the firmware
+    pub fn from_raw(raw: u8) -> Self {
+        BackButtonDelay { raw }
+    }
+
+    pub fn raw(&self) -> u8 {
+        self.raw
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.raw as u64)
+    }
+
+    pub fn is_supported(&self) -> bool {
+        Self::SUPPORTED.contains(self)
+    }
+}
+
+impl TryFrom<Duration> for BackButtonDelay {
+    type Error = String;
+
+    fn try_from(value: Duration) -> Result<Self, Self::Error> {
+        BackButtonDelay::from_duration(value)
+    }
+}
+
+impl From<BackButtonDelay> for u8 {
+    fn from(value: BackButtonDelay) -> Self {
+        value.raw
+    }
+}
+
+impl From<BackButtonDelay> for Duration {
+    fn from(value: BackButtonDelay) -> Self {
+        value.duration()
+    }
+}
+
+/// Parse a duration like `100ms` or `0.1s`, a plain number of milliseconds, or `raw:<byte>` in
+/// decimal or hexadecimal
+impl FromStr for BackButtonDelay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(raw) = s.strip_prefix("raw:") {
+            return parse_hex(raw.trim())
+                .map(|v| BackButtonDelay::from_raw(v.into()))
+                .map_err(|_| {
+                    format!(
+                        "invalid raw byte {}, expected 0..=255 or 0x00..=0xFF",
+                        raw.trim()
+                    )
+                });
+        }
+        let duration = match s.parse::<u64>() {
+            Ok(millis) => Duration::from_millis(millis),
+            Err(_) => {
+                humantime::parse_duration(s).map_err(|e| format!("invalid delay {}: {}", s, e))?
+            }
+        };
+        BackButtonDelay::from_duration(duration)
+            .map_err(|e| format!("{}, use raw:<byte> to force", e))
+    }
+}
+
+impl Display for BackButtonDelay {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_supported() {
+            write!(f, "{}ms", self.raw)
+        } else {
+            write!(f, "raw:{:#04X}", self.raw)
+        }
+    }
+}
+
+impl Serialize for BackButtonDelay {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for BackButtonDelay {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DelayVisitor;
+
+        impl<'de> Visitor<'de> for DelayVisitor {
+            type Value = BackButtonDelay;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a delay like \"100ms\" or \"raw:<byte>\"")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                BackButtonDelay::from_duration(Duration::from_millis(v))
+                    .map_err(|e| E::custom(format!("{}, use \"raw:<byte>\" to force", e)))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                u64::try_from(v)
+                    .map_err(E::custom)
+                    .and_then(|v| self.visit_u64(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DelayVisitor)
+    }
 }
@@ -1,9 +1,23 @@
-use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::de::{Error, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
 
+use crate::parse_hex;
+
 #[derive(
-    Debug, Copy, Clone, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
 )]
 #[serde(default)]
 pub struct DeadZonesConfig {
@@ -12,10 +26,181 @@ pub struct DeadZonesConfig {
 }
 
 #[derive(
-    Debug, Copy, Clone, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
 )]
 #[serde(default)]
 pub struct SpecificDeadZone {
-    pub center: i8,
-    pub border: i8,
+    pub center: DeadZone,
+    pub border: DeadZone,
+}
+
+/// Dead zone adjustment in percent of the stick travel, in range -10..=10
+///
+/// GPD WinControls presents the value as -10% to +10%, `0` keeps the factory dead zone,
+/// positive values enlarge it and negative values shrink it.
+/// The firmware stores it as a two's complement byte, e.g. `-10` is `0xF6`.
+/// Bytes outside the range can only be created with [DeadZone::from_raw], or `"raw:<byte>"` as string.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeadZone {
+    value: i8,
+}
+
+impl DeadZone {
+    pub const MIN: i8 = -10;
+    pub const MAX: i8 = 10;
+
+    pub fn new(value: i8) -> Result<Self, String> {
+        if (Self::MIN..=Self::MAX).contains(&value) {
+            Ok(DeadZone { value })
+        } else {
+            Err(format!(
+                "dead zone {} is not in range {}..={}",
+                value,
+                Self::MIN,
+                Self::MAX
+            ))
+        }
+    }
+
+    /// Escape hatch for bytes outside the range, e.g. read from the device
+    pub fn from_raw(raw: u8) -> Self {
+        DeadZone { value: raw as i8 }
+    }
+
+    pub fn raw(&self) -> u8 {
+        self.value as u8
+    }
+
+    pub fn value(&self) -> i8 {
+        self.value
+    }
+
+    pub fn is_supported(&self) -> bool {
+        (Self::MIN..=Self::MAX).contains(&self.value)
+    }
+
+    /// The adjustment in percent of the stick travel
+    pub fn percent(&self) -> f32 {
+        self.value as f32
+    }
+}
+
+impl TryFrom<i8> for DeadZone {
+    type Error = String;
+
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        DeadZone::new(value)
+    }
+}
+
+impl From<DeadZone> for i8 {
+    fn from(value: DeadZone) -> Self {
+        value.value
+    }
+}
+
+impl From<DeadZone> for u8 {
+    fn from(value: DeadZone) -> Self {
+        value.value as u8
+    }
+}
+
+/// Parse `-10` to `10` with an optional `%`, or `raw:<byte>` in decimal or hexadecimal
+impl FromStr for DeadZone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(raw) = s.strip_prefix("raw:") {
+            return parse_hex(raw.trim())
+                .map(|v| DeadZone::from_raw(v.into()))
+                .map_err(|_| {
+                    format!(
+                        "invalid raw byte {}, expected 0..=255 or 0x00..=0xFF",
+                        raw.trim()
+                    )
+                });
+        }
+        s.trim_end_matches('%')
+            .parse::<i8>()
+            .map_err(|e| format!("{}: {}", s, e))
+            .and_then(DeadZone::new)
+            .map_err(|e| format!("{}, use raw:<byte> to force", e))
+    }
+}
+
+impl Display for DeadZone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_supported() {
+            write!(f, "{}", self.value)
+        } else {
+            write!(f, "raw:{:#04X}", self.raw())
+        }
+    }
+}
+
+impl Serialize for DeadZone {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.is_supported() {
+            serializer.serialize_i8(self.value)
+        } else {
+            serializer.serialize_str(self.to_string().as_str())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DeadZone {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DeadZoneVisitor;
+
+        impl<'de> Visitor<'de> for DeadZoneVisitor {
+            type Value = DeadZone;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a dead zone from -10 to 10 or \"raw:<byte>\"")
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                i8::try_from(v)
+                    .map_err(E::custom)
+                    .and_then(|v| DeadZone::new(v).map_err(E::custom))
+                    .map_err(|e| E::custom(format!("{}, use \"raw:<byte>\" to force", e)))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                i64::try_from(v)
+                    .map_err(E::custom)
+                    .and_then(|v| self.visit_i64(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(DeadZoneVisitor)
+    }
 }
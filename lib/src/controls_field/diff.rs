@@ -0,0 +1,51 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::controls_field::ControlsConfig;
+
+/// A field whose value differs between two configs
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    /// Dotted path of the field, e.g. `dead_zones.left.center`
+    pub path: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.path, self.old, self.new)
+    }
+}
+
+impl ControlsConfig {
+    /// Every field changed from `self` to `other`, in declaration order
+    pub fn diff(&self, other: &ControlsConfig) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        if let (Ok(old), Ok(new)) = (serde_json::to_value(self), serde_json::to_value(other)) {
+            diff(&old, &new, "", &mut changes);
+        }
+        changes
+    }
+}
+
+fn diff(old: &Value, new: &Value, path: &str, changes: &mut Vec<FieldChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => old.iter().for_each(|(key, old)| {
+            let path = if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            diff(old, new.get(key).unwrap_or(&Value::Null), &path, changes)
+        }),
+        (old, new) if old != new => changes.push(FieldChange {
+            path: path.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::controls_field::parse::{from_deserializer, ParseMode};
+use crate::controls_field::registry::{Field, FIELDS};
+use crate::controls_field::ControlsConfig;
+use crate::error::Error;
+
+/// Text formats a [ControlsConfig] profile can be stored in
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ConfigFormat {
+    #[default]
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Detect the format from the file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Canonical file extension
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+        }
+    }
+
+    /// Use `format` if given, otherwise detect it from `path`, falling back to JSON
+    pub fn resolve(format: Option<Self>, path: &Path) -> Self {
+        format.or_else(|| Self::from_path(path)).unwrap_or_default()
+    }
+
+    /// Deserialize any document, see [from_deserializer] for how unknown fields are handled
+    pub fn deserialize<T>(&self, s: &str, mode: ParseMode) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Serialize + Default,
+    {
+        match self {
+            ConfigFormat::Json => {
+                let mut deserializer = serde_json::Deserializer::from_str(s);
+                let value = from_deserializer(&mut deserializer, mode)?;
+                deserializer
+                    .end()
+                    .map_err(|e| Error::validation(e.to_string()))?;
+                Ok(value)
+            }
+            ConfigFormat::Toml => from_deserializer(toml::Deserializer::new(s), mode),
+            ConfigFormat::Yaml => from_deserializer(serde_yaml::Deserializer::from_str(s), mode),
+        }
+    }
+
+    /// Serialize any value, e.g. a whole [ControlsConfig] or a single section of it
+    pub fn serialize<T: Serialize>(&self, value: &T) -> Result<String, Error> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        }
+        .map_err(Error::from)
+    }
+}
+
+impl ControlsConfig {
+    pub fn from_str_with(s: &str, format: ConfigFormat, mode: ParseMode) -> Result<Self, Error> {
+        format.deserialize(s, mode)
+    }
+
+    pub fn to_string_with(&self, format: ConfigFormat) -> Result<String, Error> {
+        format.serialize(self)
+    }
+
+    /// Like [ControlsConfig::to_string_with], every field gets a comment with its description
+    /// and allowed values
+    ///
+    /// JSON has no comments, its `//` comments are those of JSONC and have to be removed before
+    /// the document is parsed again.
+    pub fn to_commented_string(&self, format: ConfigFormat) -> Result<String, Error> {
+        let s = self.to_string_with(format)?;
+        match format {
+            ConfigFormat::Json => return Ok(comment_lines(&s, "//")),
+            ConfigFormat::Yaml => return Ok(comment_lines(&s, "#")),
+            ConfigFormat::Toml => {}
+        }
+        let mut document = s
+            .parse::<DocumentMut>()
+            .map_err(|e| Error::validation(e.to_string()))?;
+        FIELDS.iter().for_each(|field| {
+            let (parents, key) = field.path.rsplit_once('.').unwrap_or(("", field.path));
+            let table = parents
+                .split('.')
+                .filter(|parent| !parent.is_empty())
+                .try_fold(document.as_table_mut(), |table, parent| {
+                    table.get_mut(parent)?.as_table_mut()
+                });
+            if let Some(mut key) = table.and_then(|table| table.key_mut(key)) {
+                key.leaf_decor_mut()
+                    .set_prefix(format!("# {}\n", field_comment(field)));
+            }
+        });
+        Ok(document.to_string())
+    }
+
+    /// Write this config into an existing document of the same format
+    ///
+    /// For TOML only changed values are replaced, so comments and layout of `existing` survive.
+    /// JSON has no comments and YAML comments can't be preserved, both are rewritten entirely.
+    pub fn update_document(&self, existing: &str, format: ConfigFormat) -> Result<String, Error> {
+        match format {
+            ConfigFormat::Toml => {
+                let mut document = existing
+                    .parse::<DocumentMut>()
+                    .map_err(|e| Error::validation(e.to_string()))?;
+                let updated = self
+                    .to_string_with(ConfigFormat::Toml)?
+                    .parse::<DocumentMut>()
+                    .map_err(|e| Error::validation(e.to_string()))?;
+                merge_toml(document.as_table_mut(), updated.as_table());
+                Ok(document.to_string())
+            }
+            other => self.to_string_with(other),
+        }
+    }
+}
+
+fn field_comment(field: &Field) -> String {
+    format!("{}: {}", field.description, field.kind.allowed())
+}
+
+/// Put a comment above every field of a pretty printed JSON or block style YAML document,
+/// whose nesting is told by the indentation of the keys
+fn comment_lines(s: &str, marker: &str) -> String {
+    let mut parents: Vec<(usize, &str)> = Vec::new();
+    let mut commented = String::new();
+    for line in s.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let key = line
+            .trim_start()
+            .split_once(':')
+            .map(|(key, _)| key.trim_matches('"'))
+            .filter(|key| key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        if let Some(key) = key {
+            parents.retain(|(parent, _)| *parent < indent);
+            let path = parents
+                .iter()
+                .map(|(_, parent)| *parent)
+                .chain([key])
+                .collect::<Vec<&str>>()
+                .join(".");
+            if let Some(field) = FIELDS.iter().find(|field| field.path == path) {
+                commented.push_str(&format!(
+                    "{}{} {}\n",
+                    &line[..indent],
+                    marker,
+                    field_comment(field)
+                ));
+            }
+            parents.push((indent, key));
+        }
+        commented.push_str(line);
+        commented.push('\n');
+    }
+    commented
+}
+
+fn merge_toml(old: &mut Table, new: &Table) {
+    new.iter()
+        .for_each(|(key, item)| match (old.get_mut(key), item) {
+            (Some(Item::Table(old)), Item::Table(new)) => merge_toml(old, new),
+            (Some(Item::Value(old)), Item::Value(new)) => {
+                if old.to_string().trim() != new.to_string().trim() {
+                    let decor = old.decor().clone();
+                    *old = new.clone();
+                    *old.decor_mut() = decor;
+                }
+            }
+            _ => {
+                old.insert(key, item.clone());
+            }
+        });
+}
@@ -1,5 +1,8 @@
+use std::str::FromStr;
+
 use clap::ValueEnum;
-use serde::de::Error;
+use serde::de::value::Error as ValueError;
+use serde::de::{Error, IntoDeserializer};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
@@ -47,10 +50,25 @@ impl<'de> Deserialize<'de> for HIDUsageIDu8 {
     where
         D: Deserializer<'de>,
     {
-        let s = String::deserialize(deserializer)?;
-        HIDUsageID::from_str(&s, true).map_or_else(
-            |_| -> Result<Self, D::Error> { parse_hex(&s).map_err(|e| D::Error::custom(e)) },
-            |v| -> Result<Self, _> { Ok(v.into()) },
-        )
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Parse a HID usage name, case insensitive, or an id in decimal or hexadecimal
+impl FromStr for HIDUsageIDu8 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Serialized names of acronyms differ from the CLI names, e.g. `keypad-x-o-r`
+        HIDUsageID::from_str(s, true)
+            .ok()
+            .or_else(|| {
+                HIDUsageID::deserialize(s.into_deserializer())
+                    .map_err(|_: ValueError| ())
+                    .ok()
+            })
+            .map_or_else(|| parse_hex(s), |v| Ok(v.into()))
     }
 }
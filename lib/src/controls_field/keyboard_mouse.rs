@@ -5,7 +5,15 @@ use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
 use crate::controls_field::hid_usage_id_u8::HIDUsageIDu8;
 
 #[derive(
-    Debug, Copy, Clone, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
 )]
 #[serde(default)]
 pub struct KeyboardMouseConfig {
@@ -19,7 +27,15 @@ pub struct KeyboardMouseConfig {
 }
 
 #[derive(
-    Debug, Copy, Clone, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
 )]
 #[serde(default)]
 pub struct DirectionalPadConfig {
@@ -30,7 +46,15 @@ pub struct DirectionalPadConfig {
 }
 
 #[derive(
-    Debug, Copy, Clone, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
 )]
 #[serde(default)]
 pub struct LeftStickConfig {
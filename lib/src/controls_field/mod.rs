@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
-use serde::{Deserialize, Serialize, Serializer};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
 
@@ -11,10 +12,20 @@ use crate::enums::Vibrate;
 
 pub mod back_button;
 pub mod dead_zones;
+pub mod diff;
+pub mod format;
 pub mod hid_usage_id_u8;
 pub mod keyboard_mouse;
+pub mod parse;
+pub mod patch;
+pub mod registry;
+pub mod share_code;
+pub mod validation;
 
-#[derive(Copy, Clone, Debug, Serialize, PrettyJsonSerializeDisplayAdapter)]
+#[derive(
+    Copy, Clone, Debug, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+)]
+#[serde(default)]
 pub struct ControlsField {
     pub firmware_version: FirmwareVersion,
     pub keyboard_mouse: KeyboardMouseConfig,
@@ -24,7 +35,7 @@ pub struct ControlsField {
     pub checksum: Checksum,
 }
 
-#[derive(Copy, Clone, Debug, PrettyJsonSerializeDisplayAdapter)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PrettyJsonSerializeDisplayAdapter)]
 pub struct Checksum {
     value: u64,
 }
@@ -44,13 +55,47 @@ impl Serialize for Checksum {
     }
 }
 
-#[derive(Copy, Clone, Debug, Serialize, PrettyJsonSerializeDisplayAdapter)]
+impl<'de> Deserialize<'de> for Checksum {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        u64::from_str_radix(s.trim_start_matches("0x").trim_start_matches("0X"), 16)
+            .map(Checksum::from)
+            .map_err(|e| D::Error::custom(format!("invalid checksum {}: {}", s, e)))
+    }
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
+)]
 pub struct FirmwareVersion {
     pub gamepad_firmware: Version,
     pub keyboard_mouse_firmware: Version,
 }
 
-#[derive(Copy, Clone, Debug, PrettyJsonSerializeDisplayAdapter)]
+impl FirmwareVersion {
+    /// e.g. `gamepad 1.2 keyboard mouse 3.4`
+    pub fn summary(&self) -> String {
+        let version = |v: &Version| format!("{}.{}", v.major_version, v.minor_version);
+        format!(
+            "gamepad {} keyboard mouse {}",
+            version(&self.gamepad_firmware),
+            version(&self.keyboard_mouse_firmware)
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PrettyJsonSerializeDisplayAdapter)]
 pub struct Version {
     pub major_version: u8,
     pub minor_version: u8,
@@ -65,8 +110,33 @@ impl Serialize for Version {
     }
 }
 
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.split_once('.')
+            .and_then(|(major, minor)| {
+                Some(Version {
+                    major_version: major.parse().ok()?,
+                    minor_version: minor.parse().ok()?,
+                })
+            })
+            .ok_or_else(|| D::Error::custom(format!("invalid version {}, expect MAJOR.MINOR", s)))
+    }
+}
+
 #[derive(
-    Copy, Clone, Debug, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter,
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PrettyJsonSerializeDisplayAdapter,
 )]
 #[serde(default)]
 pub struct ControlsConfig {
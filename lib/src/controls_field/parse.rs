@@ -0,0 +1,156 @@
+use log::warn;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::controls_field::format::ConfigFormat;
+use crate::controls_field::ControlsConfig;
+use crate::error::Error;
+
+/// How to treat fields of a config file which are not part of [ControlsConfig]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Reject unknown fields and suggest the closest known field name
+    #[default]
+    Strict,
+    /// Ignore unknown fields with a warning, for configs written by newer versions
+    Lenient,
+}
+
+impl From<bool> for ParseMode {
+    fn from(lenient: bool) -> Self {
+        if lenient {
+            ParseMode::Lenient
+        } else {
+            ParseMode::Strict
+        }
+    }
+}
+
+impl ControlsConfig {
+    pub fn from_json(s: &str, mode: ParseMode) -> Result<Self, Error> {
+        ConfigFormat::Json.deserialize(s, mode)
+    }
+
+    pub fn from_deserializer<'de, D>(deserializer: D, mode: ParseMode) -> Result<Self, Error>
+    where
+        D: Deserializer<'de>,
+    {
+        from_deserializer(deserializer, mode)
+    }
+}
+
+/// Deserialize any document, e.g. a [ControlsConfig] or a profile, reporting unknown fields
+///
+/// Known field names are taken from the serialized `T::default()`.
+pub fn from_deserializer<'de, T, D>(deserializer: D, mode: ParseMode) -> Result<T, Error>
+where
+    T: Deserialize<'de> + Serialize + Default,
+    D: Deserializer<'de>,
+{
+    let mut unknown: Vec<String> = Vec::new();
+    let value = serde_ignored::deserialize(deserializer, |path| unknown.push(path.to_string()))
+        .map_err(|e: D::Error| Error::validation(e.to_string()))?;
+    if unknown.is_empty() {
+        return Ok(value);
+    }
+    let known = serde_json::to_value(T::default()).unwrap_or_default();
+    let messages = unknown.iter().map(|path| {
+        suggest(&known, path).map_or_else(
+            || format!("unknown field `{}`", path),
+            |v| format!("unknown field `{}`, did you mean `{}`?", path, v),
+        )
+    });
+    match mode {
+        ParseMode::Lenient => {
+            messages.for_each(|m| warn!("{}, ignored", m));
+            Ok(value)
+        }
+        ParseMode::Strict => Err(Error::validation(
+            messages.collect::<Vec<String>>().join("\n"),
+        )),
+    }
+}
+
+/// Find the known sibling field with the most similar name
+fn suggest(known: &Value, path: &str) -> Option<String> {
+    let (parent, key) = path.rsplit_once('.').unwrap_or(("", path));
+    let known = parent
+        .split('.')
+        .filter(|i| !i.is_empty())
+        .try_fold(known, |value, i| value.get(i))?;
+    let Value::Object(fields) = known else {
+        return None;
+    };
+    fields
+        .keys()
+        .map(|field| (strsim::jaro_winkler(key, field), field))
+        .filter(|(similarity, _)| *similarity > 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, field)| {
+            if parent.is_empty() {
+                field.to_string()
+            } else {
+                format!("{}.{}", parent, field)
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseMode;
+    use crate::controls_field::back_button::BackButtonDelay;
+    use crate::controls_field::dead_zones::DeadZone;
+    use crate::controls_field::format::ConfigFormat;
+    use crate::controls_field::ControlsConfig;
+    use crate::error::ErrorCategory;
+
+    #[test]
+    fn unknown_fields_get_suggestions() {
+        let e = ControlsConfig::from_json(r#"{"back_buton": {}}"#, ParseMode::Strict).unwrap_err();
+        assert_eq!(e.category, ErrorCategory::Validation);
+        assert_eq!(
+            e.message,
+            "unknown field `back_buton`, did you mean `back_button`?"
+        );
+        let e = ControlsConfig::from_json(
+            r#"{"back_button": {"left": {"firts": "f13"}}}"#,
+            ParseMode::Strict,
+        )
+        .unwrap_err();
+        assert_eq!(
+            e.message,
+            "unknown field `back_button.left.firts`, did you mean `back_button.left.first`?"
+        );
+        let e = ControlsConfig::from_json(r#"{"colour": "red"}"#, ParseMode::Strict).unwrap_err();
+        assert_eq!(e.message, "unknown field `colour`");
+    }
+
+    #[test]
+    fn lenient_ignores_unknown_fields() {
+        let config = ControlsConfig::from_json(
+            r#"{"colour": "red", "dead_zones": {"left": {"center": 5}}}"#,
+            ParseMode::Lenient,
+        )
+        .unwrap();
+        assert_eq!(config.dead_zones.left.center.value(), 5);
+    }
+
+    #[test]
+    fn raw_values_round_trip() {
+        let mut config = ControlsConfig::default();
+        config.dead_zones.left.center = DeadZone::from_raw(0x40);
+        config.back_button.left.first_delay = BackButtonDelay::from_raw(7);
+        [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml]
+            .into_iter()
+            .for_each(|format| {
+                let s = config.to_string_with(format).unwrap();
+                assert!(s.contains("raw:0x40"), "{:?}: {}", format, s);
+                assert_eq!(
+                    ControlsConfig::from_str_with(&s, format, ParseMode::Strict),
+                    Ok(config),
+                    "{:?}",
+                    format
+                );
+            });
+    }
+}
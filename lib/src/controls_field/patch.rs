@@ -0,0 +1,93 @@
+use json_patch::Patch;
+use serde_json::Value;
+
+use crate::controls_field::parse::ParseMode;
+use crate::controls_field::ControlsConfig;
+use crate::error::Error;
+
+impl ControlsConfig {
+    /// Apply a patch document on top of this config
+    ///
+    /// A JSON object is applied as [RFC 7396](https://www.rfc-editor.org/rfc/rfc7396) merge patch,
+    /// a JSON array as [RFC 6902](https://www.rfc-editor.org/rfc/rfc6902) JSON Patch.
+    pub fn apply_patch(&self, patch: &Value, mode: ParseMode) -> Result<ControlsConfig, Error> {
+        match patch {
+            Value::Array(_) => {
+                let patch: Patch = serde_json::from_value(patch.clone())
+                    .map_err(|e| Error::validation(e.to_string()))?;
+                self.json_patch(&patch, mode)
+            }
+            Value::Object(_) => self.merge_patch(patch, mode),
+            _ => Err(Error::validation(
+                "Patch must be a JSON object (merge patch) or array (JSON Patch)",
+            )),
+        }
+    }
+
+    /// Apply an RFC 7396 merge patch, `null` resets a field to its default
+    pub fn merge_patch(&self, patch: &Value, mode: ParseMode) -> Result<ControlsConfig, Error> {
+        let mut document = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        json_patch::merge(&mut document, patch);
+        ControlsConfig::from_deserializer(document, mode)
+    }
+
+    /// Apply an RFC 6902 JSON Patch, paths are JSON pointers like `/back_button/left/first`
+    pub fn json_patch(&self, patch: &Patch, mode: ParseMode) -> Result<ControlsConfig, Error> {
+        let mut document = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        json_patch::patch(&mut document, patch).map_err(|e| Error::validation(e.to_string()))?;
+        ControlsConfig::from_deserializer(document, mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::controls_field::parse::ParseMode;
+    use crate::controls_field::ControlsConfig;
+    use crate::profile::preset::PRESETS;
+
+    #[test]
+    fn merge_patch_and_json_patch_agree() {
+        let config = PRESETS[1].config();
+        let merged = config
+            .apply_patch(
+                &json!({"dead_zones": {"left": {"center": 3}}}),
+                ParseMode::Strict,
+            )
+            .unwrap();
+        let patched = config
+            .apply_patch(
+                &json!([{"op": "replace", "path": "/dead_zones/left/center", "value": 3}]),
+                ParseMode::Strict,
+            )
+            .unwrap();
+        assert_eq!(merged, patched);
+        assert_eq!(merged.dead_zones.left.center.value(), 3);
+        assert_eq!(merged.keyboard_mouse, config.keyboard_mouse);
+    }
+
+    #[test]
+    fn merge_patch_null_resets_to_default() {
+        let config = PRESETS[1].config();
+        let patched = config
+            .apply_patch(&json!({"keyboard_mouse": null}), ParseMode::Strict)
+            .unwrap();
+        assert_ne!(
+            config.keyboard_mouse,
+            ControlsConfig::default().keyboard_mouse
+        );
+        assert_eq!(
+            patched.keyboard_mouse,
+            ControlsConfig::default().keyboard_mouse
+        );
+        assert_eq!(patched.back_button, config.back_button);
+    }
+
+    #[test]
+    fn patch_must_be_object_or_array() {
+        assert!(ControlsConfig::default()
+            .apply_patch(&json!("wasd"), ParseMode::Strict)
+            .is_err());
+    }
+}
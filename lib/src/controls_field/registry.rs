@@ -0,0 +1,349 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::controls_field::back_button::BackButtonDelay;
+use crate::controls_field::dead_zones::DeadZone;
+use crate::controls_field::hid_usage_id_u8::HIDUsageIDu8;
+use crate::controls_field::ControlsConfig;
+use crate::enums::Vibrate;
+use crate::error::Error;
+
+/// Type of the value a [Field] holds
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FieldKind {
+    Key,
+    Delay,
+    Vibrate,
+    DeadZone,
+}
+
+impl FieldKind {
+    /// Values accepted by [FieldKind::parse]
+    pub fn allowed(&self) -> String {
+        match self {
+            FieldKind::Key => "HID usage name or id 0x00..=0xFF".to_string(),
+            FieldKind::Delay => BackButtonDelay::SUPPORTED
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            FieldKind::Vibrate => Vibrate::value_variants()
+                .iter()
+                .filter_map(|v| Some(v.to_possible_value()?.get_name().to_string()))
+                .collect::<Vec<String>>()
+                .join(", "),
+            FieldKind::DeadZone => format!("{}..={}", DeadZone::MIN, DeadZone::MAX),
+        }
+    }
+
+    pub fn parse(&self, s: &str) -> Result<FieldValue, String> {
+        match self {
+            FieldKind::Key => s.parse().map(FieldValue::Key),
+            FieldKind::Delay => s.parse().map(FieldValue::Delay),
+            FieldKind::Vibrate => Vibrate::from_str(s, true).map(FieldValue::Vibrate),
+            FieldKind::DeadZone => s.parse().map(FieldValue::DeadZone),
+        }
+    }
+}
+
+/// Value of any [Field], serialized like the field inside [ControlsConfig]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Key(HIDUsageIDu8),
+    Delay(BackButtonDelay),
+    Vibrate(Vibrate),
+    DeadZone(DeadZone),
+}
+
+impl FieldValue {
+    pub fn kind(&self) -> FieldKind {
+        match self {
+            FieldValue::Key(_) => FieldKind::Key,
+            FieldValue::Delay(_) => FieldKind::Delay,
+            FieldValue::Vibrate(_) => FieldKind::Vibrate,
+            FieldValue::DeadZone(_) => FieldKind::DeadZone,
+        }
+    }
+}
+
+/// Same text as in a config file, without quotes
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::String(s)) => write!(f, "{}", s),
+            Ok(value) => write!(f, "{}", value),
+            Err(_) => Err(std::fmt::Error),
+        }
+    }
+}
+
+/// A configurable field of [ControlsConfig]
+pub struct Field {
+    /// Dotted path, same as in a config file, e.g. `back_button.left.first`
+    pub path: &'static str,
+    pub kind: FieldKind,
+    pub description: &'static str,
+    get: fn(&ControlsConfig) -> FieldValue,
+    set: fn(&mut ControlsConfig, FieldValue),
+}
+
+impl Field {
+    pub fn get(&self, config: &ControlsConfig) -> FieldValue {
+        (self.get)(config)
+    }
+
+    pub fn set(&self, config: &mut ControlsConfig, value: FieldValue) -> Result<(), Error> {
+        if value.kind() != self.kind {
+            return Err(Error::validation(format!(
+                "{} holds a {:?} value, not {:?}",
+                self.path,
+                self.kind,
+                value.kind()
+            ))
+            .with_path(self.path));
+        }
+        (self.set)(config, value);
+        Ok(())
+    }
+
+    pub fn parse(&self, s: &str) -> Result<FieldValue, Error> {
+        self.kind.parse(s).map_err(|e| {
+            Error::validation(format!(
+                "{}: {}, expect {}",
+                self.path,
+                e,
+                self.kind.allowed()
+            ))
+            .with_path(self.path)
+        })
+    }
+}
+
+/// Declare a [Field] by its position in [ControlsConfig], the path is derived from it
+macro_rules! field {
+    ($kind:ident, $description:literal, $first:ident $(. $rest:ident)*) => {
+        Field {
+            path: concat!(stringify!($first) $(, ".", stringify!($rest))*),
+            kind: FieldKind::$kind,
+            description: $description,
+            get: |config| FieldValue::$kind(config.$first$(.$rest)*),
+            set: |config, value| {
+                if let FieldValue::$kind(value) = value {
+                    config.$first$(.$rest)* = value;
+                }
+            },
+        }
+    };
+}
+
+/// Declare every field as static and [FIELDS] listing them, in the given order
+macro_rules! fields {
+    ($($name:ident: $kind:ident, $description:literal, $first:ident $(. $rest:ident)*;)*) => {
+        $(pub static $name: Field = field!($kind, $description, $first $(. $rest)*);)*
+
+        /// Every configurable field, in declaration order of [ControlsConfig]
+        pub static FIELDS: &[&Field] = &[$(&$name),*];
+    };
+}
+
+fields! {
+    DPAD_UP: Key, "D-pad up", keyboard_mouse.directional_pad.up;
+    DPAD_DOWN: Key, "D-pad down", keyboard_mouse.directional_pad.down;
+    DPAD_LEFT: Key, "D-pad left", keyboard_mouse.directional_pad.left;
+    DPAD_RIGHT: Key, "D-pad right", keyboard_mouse.directional_pad.right;
+    A_BUTTON: Key, "A button", keyboard_mouse.a;
+    B_BUTTON: Key, "B button", keyboard_mouse.b;
+    X_BUTTON: Key, "X button", keyboard_mouse.x;
+    Y_BUTTON: Key, "Y button", keyboard_mouse.y;
+    LEFT_STICK_UP: Key, "Left stick up", keyboard_mouse.left_stick.up;
+    LEFT_STICK_DOWN: Key, "Left stick down", keyboard_mouse.left_stick.down;
+    LEFT_STICK_LEFT: Key, "Left stick left", keyboard_mouse.left_stick.left;
+    LEFT_STICK_RIGHT: Key, "Left stick right", keyboard_mouse.left_stick.right;
+    LEFT_STICK_PUSH: Key, "Left stick push", keyboard_mouse.left_stick.push;
+    RIGHT_STICK_PUSH: Key, "Right stick push", keyboard_mouse.right_stick_push;
+    BACK_BUTTON_LEFT_FIRST: Key, "Left back button, 1st key", back_button.left.first;
+    BACK_BUTTON_LEFT_SECOND: Key, "Left back button, 2nd key", back_button.left.second;
+    BACK_BUTTON_LEFT_THIRD: Key, "Left back button, 3rd key", back_button.left.third;
+    BACK_BUTTON_LEFT_FORTH: Key, "Left back button, 4th key", back_button.left.forth;
+    BACK_BUTTON_LEFT_FIRST_DELAY: Delay, "Left back button, delay after 1st key", back_button.left.first_delay;
+    BACK_BUTTON_LEFT_SECOND_DELAY: Delay, "Left back button, delay after 2nd key", back_button.left.second_delay;
+    BACK_BUTTON_LEFT_THIRD_DELAY: Delay, "Left back button, delay after 3rd key", back_button.left.third_delay;
+    BACK_BUTTON_RIGHT_FIRST: Key, "Right back button, 1st key", back_button.right.first;
+    BACK_BUTTON_RIGHT_SECOND: Key, "Right back button, 2nd key", back_button.right.second;
+    BACK_BUTTON_RIGHT_THIRD: Key, "Right back button, 3rd key", back_button.right.third;
+    BACK_BUTTON_RIGHT_FORTH: Key, "Right back button, 4th key", back_button.right.forth;
+    BACK_BUTTON_RIGHT_FIRST_DELAY: Delay, "Right back button, delay after 1st key", back_button.right.first_delay;
+    BACK_BUTTON_RIGHT_SECOND_DELAY: Delay, "Right back button, delay after 2nd key", back_button.right.second_delay;
+    BACK_BUTTON_RIGHT_THIRD_DELAY: Delay, "Right back button, delay after 3rd key", back_button.right.third_delay;
+    VIBRATE: Vibrate, "Vibration strength", vibrate;
+    DEAD_ZONE_LEFT_CENTER: DeadZone, "Left stick center dead zone in percent", dead_zones.left.center;
+    DEAD_ZONE_LEFT_BORDER: DeadZone, "Left stick border dead zone in percent", dead_zones.left.border;
+    DEAD_ZONE_RIGHT_CENTER: DeadZone, "Right stick center dead zone in percent", dead_zones.right.center;
+    DEAD_ZONE_RIGHT_BORDER: DeadZone, "Right stick border dead zone in percent", dead_zones.right.border;
+}
+
+/// Look up a field by its path, or by a suffix of it matching only one field, e.g. `a`
+pub fn find(path: &str) -> Result<&'static Field, Error> {
+    if let Some(field) = FIELDS.iter().find(|field| field.path == path) {
+        return Ok(*field);
+    }
+    let suffix = format!(".{}", path);
+    let matches: Vec<&Field> = FIELDS
+        .iter()
+        .copied()
+        .filter(|field| field.path.ends_with(&suffix))
+        .collect();
+    match matches.as_slice() {
+        [field] => Ok(field),
+        [] => Err(Error::validation(
+            FIELDS
+                .iter()
+                .map(|field| (strsim::jaro_winkler(path, field.path), field.path))
+                .filter(|(similarity, _)| *similarity > 0.8)
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+                .map_or_else(
+                    || format!("unknown field `{}`", path),
+                    |(_, v)| format!("unknown field `{}`, did you mean `{}`?", path, v),
+                ),
+        )),
+        fields => Err(Error::validation(format!(
+            "field `{}` is ambiguous, it may be {}",
+            path,
+            fields
+                .iter()
+                .map(|field| field.path)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ))),
+    }
+}
+
+impl ControlsConfig {
+    pub fn get(&self, path: &str) -> Result<FieldValue, Error> {
+        Ok(find(path)?.get(self))
+    }
+
+    /// Parse `value` according to the type of the field and set it
+    pub fn set(&mut self, path: &str, value: &str) -> Result<(), Error> {
+        let field = find(path)?;
+        field.set(self, field.parse(value)?)
+    }
+}
+
+/// A `path=value` pair, e.g. `back_button.left.first=F13`, spaces around `=` are ignored
+#[derive(Copy, Clone)]
+pub struct Assignment {
+    pub field: &'static Field,
+    pub value: FieldValue,
+}
+
+impl Assignment {
+    pub fn apply(&self, config: &mut ControlsConfig) -> Result<(), Error> {
+        self.field.set(config, self.value)
+    }
+}
+
+impl FromStr for Assignment {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, value) = s
+            .split_once('=')
+            .ok_or_else(|| Error::validation(format!("expect path=value, got `{}`", s)))?;
+        let field = find(path.trim())?;
+        Ok(Assignment {
+            field,
+            value: field.parse(value.trim())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, Assignment, FIELDS};
+    use crate::controls_field::ControlsConfig;
+    use crate::profile::preset::PRESETS;
+
+    #[test]
+    fn paths_match_the_serialized_config() {
+        let config = PRESETS[1].config();
+        let value = serde_json::to_value(config).unwrap();
+        FIELDS.iter().for_each(|field| {
+            let serialized = field
+                .path
+                .split('.')
+                .try_fold(&value, |value, key| value.get(key))
+                .unwrap_or_else(|| panic!("{} is not serialized", field.path));
+            assert_eq!(
+                serde_json::to_value(field.get(&config)).unwrap(),
+                *serialized,
+                "{}",
+                field.path
+            );
+        });
+    }
+
+    #[test]
+    fn find_by_unique_suffix() {
+        assert_eq!(find("a").unwrap().path, "keyboard_mouse.a");
+        assert_eq!(find("vibrate").unwrap().path, "vibrate");
+    }
+
+    #[test]
+    fn ambiguous_suffix_lists_candidates() {
+        let e = find("left").err().unwrap();
+        assert!(
+            e.message
+                .starts_with("field `left` is ambiguous, it may be "),
+            "{}",
+            e.message
+        );
+        assert!(e.message.contains("keyboard_mouse.directional_pad.left"));
+        assert!(e.message.contains("keyboard_mouse.left_stick.left"));
+    }
+
+    #[test]
+    fn unknown_path_gets_suggestion() {
+        assert_eq!(
+            find("dead_zones.left.centre").err().unwrap().message,
+            "unknown field `dead_zones.left.centre`, did you mean `dead_zones.left.center`?"
+        );
+    }
+
+    #[test]
+    fn set_parses_by_kind() {
+        let mut config = ControlsConfig::default();
+        config.set("back_button.left.first_delay", "0.1s").unwrap();
+        config.set("dead_zones.right.border", "raw:0x40").unwrap();
+        assert!(config.get("first_delay").is_err());
+        assert_eq!(
+            config
+                .get("back_button.left.first_delay")
+                .unwrap()
+                .to_string(),
+            "100ms"
+        );
+        assert_eq!(
+            config.get("dead_zones.right.border").unwrap().to_string(),
+            "raw:0x40"
+        );
+        let e = config
+            .set("back_button.left.first_delay", "50ms")
+            .unwrap_err();
+        assert_eq!(e.path.as_deref(), Some("back_button.left.first_delay"));
+    }
+
+    #[test]
+    fn assignment_trims_spaces() {
+        let assignment: Assignment = "dead_zones.left.center = 5%".parse().unwrap();
+        let mut config = ControlsConfig::default();
+        assignment.apply(&mut config).unwrap();
+        assert_eq!(config.dead_zones.left.center.value(), 5);
+        assert!("dead_zones.left.center".parse::<Assignment>().is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use data_encoding::BASE32_NOPAD;
+
+use crate::controls_field::ControlsConfig;
+use crate::error::Error;
+use crate::protocol::function::{decode_pages, encode_pages, CHUNK_LENGTH, CONFIG_PAGES};
+
+const PREFIX: &str = "GPD";
+const VERSION: u8 = 1;
+/// Bytes of the write pages covered by a version 1 code, the part of every page read back
+const PACKED_LENGTH: usize = CONFIG_PAGES.len() * CHUNK_LENGTH;
+/// One bit per packed byte, set if the byte is not zero
+const MASK_LENGTH: usize = PACKED_LENGTH / 8;
+
+impl ControlsConfig {
+    /// Short text form for sharing, e.g. in chat
+    ///
+    /// `GPD1:` followed by base32 of the packed write pages and a CRC-16 of them.
+    /// The first 16 bytes of the pages [encode_pages] returns are packed as a bit mask of the
+    /// non-zero bytes followed by those bytes, so unmapped controls take no space.
+    pub fn to_share_code(&self) -> String {
+        let mut data = pack(&encode_pages(self));
+        data.extend_from_slice(&crc16(&data).to_be_bytes());
+        format!("{}{}:{}", PREFIX, VERSION, BASE32_NOPAD.encode(&data))
+    }
+
+    /// Decode a share code, case, spaces and dashes are ignored
+    ///
+    /// Values the firmware is not known to accept are kept, check them with [ControlsConfig::validate].
+    pub fn from_share_code(code: &str) -> Result<Self, Error> {
+        let code: String = code
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect::<String>()
+            .to_ascii_uppercase();
+        let (version, data) = code
+            .strip_prefix(PREFIX)
+            .and_then(|code| code.split_once(':'))
+            .ok_or_else(|| {
+                Error::validation(format!("Share code must start with {}{}:", PREFIX, VERSION))
+            })?;
+        if version != VERSION.to_string() {
+            return Err(Error::validation(format!(
+                "Share code version {} is not supported, only {}",
+                version, VERSION
+            )));
+        }
+        let data = BASE32_NOPAD
+            .decode(data.as_bytes())
+            .map_err(|e| Error::validation(format!("Invalid share code: {}", e)))?;
+        let (packed, checksum) = data
+            .split_last_chunk::<2>()
+            .map(|(packed, checksum)| (packed, *checksum))
+            .ok_or_else(|| {
+                Error::validation("Invalid share code: wrong length, is it complete?")
+            })?;
+        if crc16(packed).to_be_bytes() != checksum {
+            return Err(Error::validation(
+                "Invalid share code: checksum mismatch, is it mistyped?",
+            ));
+        }
+        Ok(decode_pages(&unpack(packed)?))
+    }
+}
+
+fn pack(pages: &[[u8; 25]; 8]) -> Vec<u8> {
+    let bytes: Vec<u8> = CONFIG_PAGES
+        .iter()
+        .flat_map(|minor| pages[u8::from(*minor) as usize][..CHUNK_LENGTH].to_vec())
+        .collect();
+    let mut mask = [0u8; MASK_LENGTH];
+    bytes
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| **byte != 0)
+        .for_each(|(i, _)| mask[i / 8] |= 0x80 >> (i % 8));
+    mask.into_iter()
+        .chain(bytes.into_iter().filter(|byte| *byte != 0))
+        .collect()
+}
+
+fn unpack(packed: &[u8]) -> Result<[[u8; 25]; 8], Error> {
+    let wrong_length = || Error::validation("Invalid share code: wrong length, is it complete?");
+    let (mask, mut values) = packed
+        .split_at_checked(MASK_LENGTH)
+        .ok_or_else(wrong_length)?;
+    let mut pages = [[0u8; 25]; 8];
+    for i in (0..PACKED_LENGTH).filter(|i| mask[i / 8] & (0x80 >> (i % 8)) != 0) {
+        let (value, rest) = values.split_first().ok_or_else(wrong_length)?;
+        let page = u8::from(CONFIG_PAGES[i / CHUNK_LENGTH]) as usize;
+        pages[page][i % CHUNK_LENGTH] = *value;
+        values = rest;
+    }
+    if !values.is_empty() {
+        return Err(wrong_length());
+    }
+    Ok(pages)
+}
+
+/// CRC-16/CCITT-FALSE
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xFFFF, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc16, pack, unpack};
+    use crate::controls_field::back_button::BackButtonDelay;
+    use crate::controls_field::dead_zones::DeadZone;
+    use crate::controls_field::ControlsConfig;
+    use crate::profile::preset::PRESETS;
+    use crate::protocol::function::encode_pages;
+
+    #[test]
+    fn crc16_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn pack_keeps_only_non_zero_bytes() {
+        let pages = encode_pages(&ControlsConfig::default());
+        assert_eq!(pack(&pages), vec![0u8; 10]);
+        assert_eq!(unpack(&pack(&pages)), Ok(pages));
+    }
+
+    #[test]
+    fn share_code_round_trip() {
+        let mut configs: Vec<ControlsConfig> =
+            PRESETS.iter().map(|preset| preset.config()).collect();
+        let mut raw = PRESETS[1].config();
+        raw.back_button.right.third_delay = BackButtonDelay::from_raw(0xFF);
+        raw.dead_zones.left.border = DeadZone::from_raw(0x80);
+        configs.push(raw);
+        configs.into_iter().for_each(|config| {
+            let code = config.to_share_code();
+            assert!(code.starts_with("GPD1:"), "{}", code);
+            assert_eq!(
+                ControlsConfig::from_share_code(&code),
+                Ok(config),
+                "{}",
+                code
+            );
+            assert_eq!(
+                ControlsConfig::from_share_code(&code.to_lowercase().replace("gpd1:", "gpd1: ")),
+                Ok(config)
+            );
+        });
+    }
+
+    #[test]
+    fn share_code_rejects_mistyped_and_truncated_codes() {
+        let code = PRESETS[1].config().to_share_code();
+        let last = code.chars().last().unwrap();
+        let mistyped = format!(
+            "{}{}",
+            &code[..code.len() - 1],
+            if last == 'A' { 'B' } else { 'A' }
+        );
+        assert!(ControlsConfig::from_share_code(&mistyped).is_err());
+        assert!(ControlsConfig::from_share_code(&code[..code.len() - 8]).is_err());
+        assert!(ControlsConfig::from_share_code("GPD2:AAAA").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::{Display, Formatter};
+
+use log::warn;
+use serde::Serialize;
+
+use crate::controls_field::back_button::SpecificBackButtonConfig;
+use crate::controls_field::dead_zones::{DeadZone, SpecificDeadZone};
+use crate::controls_field::ControlsConfig;
+use crate::error::Error;
+
+/// A single value the firmware is not known to accept
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    /// JSON-style path of the field, e.g. `dead_zones.left.center`
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// What to do when a config contains illegal values
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ValidationPolicy {
+    /// Refuse to write the config
+    #[default]
+    Strict,
+    /// Log every violation as a warning and write the config anyway
+    Force,
+}
+
+impl From<bool> for ValidationPolicy {
+    fn from(force: bool) -> Self {
+        if force {
+            ValidationPolicy::Force
+        } else {
+            ValidationPolicy::Strict
+        }
+    }
+}
+
+impl ControlsConfig {
+    /// Collect all values outside the ranges documented for the firmware
+    ///
+    /// Only raw back button delays and raw dead zones may be reported
+    pub fn validate(&self) -> Result<(), Vec<Violation>> {
+        let mut violations = Vec::new();
+        validate_back_button(&mut violations, "back_button.left", &self.back_button.left);
+        validate_back_button(
+            &mut violations,
+            "back_button.right",
+            &self.back_button.right,
+        );
+        validate_dead_zone(&mut violations, "dead_zones.left", &self.dead_zones.left);
+        validate_dead_zone(&mut violations, "dead_zones.right", &self.dead_zones.right);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Validate and apply `policy` to the violations found
+    pub fn check(&self, policy: ValidationPolicy) -> Result<(), Error> {
+        match (self.validate(), policy) {
+            (Ok(()), _) => Ok(()),
+            (Err(violations), ValidationPolicy::Force) => {
+                violations.iter().for_each(|v| warn!("{}", v));
+                Ok(())
+            }
+            (Err(violations), ValidationPolicy::Strict) => Err(Error::validation(format!(
+                "Invalid config:\n{}",
+                violations
+                    .iter()
+                    .map(|v| format!("    {}", v))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ))
+            .with_path(violations[0].path.clone())),
+        }
+    }
+}
+
+fn validate_back_button(
+    violations: &mut Vec<Violation>,
+    path: &str,
+    value: &SpecificBackButtonConfig,
+) {
+    [
+        ("first_delay", value.first_delay),
+        ("second_delay", value.second_delay),
+        ("third_delay", value.third_delay),
+    ]
+    .into_iter()
+    .filter(|(_, v)| !v.is_supported())
+    .for_each(|(field, v)| {
+        violations.push(Violation {
+            path: format!("{}.{}", path, field),
+            message: format!(
+                "delay {} is not 0ms(0x00) or 100ms(0x64)",
+                v.duration().as_millis()
+            ),
+        })
+    });
+}
+
+fn validate_dead_zone(violations: &mut Vec<Violation>, path: &str, value: &SpecificDeadZone) {
+    [("center", value.center), ("border", value.border)]
+        .into_iter()
+        .filter(|(_, v)| !v.is_supported())
+        .for_each(|(field, v)| {
+            violations.push(Violation {
+                path: format!("{}.{}", path, field),
+                message: format!(
+                    "dead zone {} is not in range {}..={}",
+                    v.value(),
+                    DeadZone::MIN,
+                    DeadZone::MAX
+                ),
+            })
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidationPolicy;
+    use crate::controls_field::back_button::BackButtonDelay;
+    use crate::controls_field::dead_zones::DeadZone;
+    use crate::controls_field::ControlsConfig;
+    use crate::error::ErrorCategory;
+    use crate::profile::preset::PRESETS;
+
+    #[test]
+    fn presets_are_valid() {
+        PRESETS
+            .iter()
+            .for_each(|preset| assert_eq!(preset.config().validate(), Ok(()), "{}", preset.name));
+    }
+
+    #[test]
+    fn violations_name_their_paths() {
+        let mut config = ControlsConfig::default();
+        config.back_button.right.third_delay = BackButtonDelay::from_raw(1);
+        config.dead_zones.left.border = DeadZone::from_raw(0x7F);
+        let paths: Vec<String> = config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|v| v.path)
+            .collect();
+        assert_eq!(
+            paths,
+            ["back_button.right.third_delay", "dead_zones.left.border"]
+        );
+    }
+
+    #[test]
+    fn check_applies_policy() {
+        let mut config = ControlsConfig::default();
+        config.dead_zones.right.center = DeadZone::from_raw(0x80);
+        let e = config.check(ValidationPolicy::Strict).unwrap_err();
+        assert_eq!(e.category, ErrorCategory::Validation);
+        assert_eq!(e.path.as_deref(), Some("dead_zones.right.center"));
+        assert_eq!(config.check(ValidationPolicy::Force), Ok(()));
+    }
+}
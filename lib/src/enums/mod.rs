@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 
 use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
 
+use crate::controls_field::registry;
+use crate::controls_field::registry::Field;
+
 pub mod hid_usage_id;
 
 #[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
@@ -24,6 +27,28 @@ pub enum KeyboardMouse {
     RightStickPush,
 }
 
+impl KeyboardMouse {
+    /// The field set by this control in the [field registry](crate::controls_field::registry)
+    pub fn field(&self) -> &'static Field {
+        match self {
+            KeyboardMouse::DPadUp => &registry::DPAD_UP,
+            KeyboardMouse::DPadDown => &registry::DPAD_DOWN,
+            KeyboardMouse::DPadLeft => &registry::DPAD_LEFT,
+            KeyboardMouse::DPadRight => &registry::DPAD_RIGHT,
+            KeyboardMouse::AButton => &registry::A_BUTTON,
+            KeyboardMouse::BButton => &registry::B_BUTTON,
+            KeyboardMouse::XButton => &registry::X_BUTTON,
+            KeyboardMouse::YButton => &registry::Y_BUTTON,
+            KeyboardMouse::LeftStickUp => &registry::LEFT_STICK_UP,
+            KeyboardMouse::LeftStickDown => &registry::LEFT_STICK_DOWN,
+            KeyboardMouse::LeftStickLeft => &registry::LEFT_STICK_LEFT,
+            KeyboardMouse::LeftStickRight => &registry::LEFT_STICK_RIGHT,
+            KeyboardMouse::LeftStickPush => &registry::LEFT_STICK_PUSH,
+            KeyboardMouse::RightStickPush => &registry::RIGHT_STICK_PUSH,
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
 pub enum BackButton {
     Left1,
@@ -36,6 +61,22 @@ pub enum BackButton {
     Right4,
 }
 
+impl BackButton {
+    /// The field set by this control in the [field registry](crate::controls_field::registry)
+    pub fn field(&self) -> &'static Field {
+        match self {
+            BackButton::Left1 => &registry::BACK_BUTTON_LEFT_FIRST,
+            BackButton::Left2 => &registry::BACK_BUTTON_LEFT_SECOND,
+            BackButton::Left3 => &registry::BACK_BUTTON_LEFT_THIRD,
+            BackButton::Left4 => &registry::BACK_BUTTON_LEFT_FORTH,
+            BackButton::Right1 => &registry::BACK_BUTTON_RIGHT_FIRST,
+            BackButton::Right2 => &registry::BACK_BUTTON_RIGHT_SECOND,
+            BackButton::Right3 => &registry::BACK_BUTTON_RIGHT_THIRD,
+            BackButton::Right4 => &registry::BACK_BUTTON_RIGHT_FORTH,
+        }
+    }
+}
+
 #[derive(
     Copy,
     Clone,
@@ -67,6 +108,18 @@ pub enum DeadZone {
     RightBorder,
 }
 
+impl DeadZone {
+    /// The field set by this control in the [field registry](crate::controls_field::registry)
+    pub fn field(&self) -> &'static Field {
+        match self {
+            DeadZone::LeftCenter => &registry::DEAD_ZONE_LEFT_CENTER,
+            DeadZone::LeftBorder => &registry::DEAD_ZONE_LEFT_BORDER,
+            DeadZone::RightCenter => &registry::DEAD_ZONE_RIGHT_CENTER,
+            DeadZone::RightBorder => &registry::DEAD_ZONE_RIGHT_BORDER,
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
 pub enum BackButtonDelay {
     Left1,
@@ -76,3 +129,17 @@ pub enum BackButtonDelay {
     Right2,
     Right3,
 }
+
+impl BackButtonDelay {
+    /// The field set by this control in the [field registry](crate::controls_field::registry)
+    pub fn field(&self) -> &'static Field {
+        match self {
+            BackButtonDelay::Left1 => &registry::BACK_BUTTON_LEFT_FIRST_DELAY,
+            BackButtonDelay::Left2 => &registry::BACK_BUTTON_LEFT_SECOND_DELAY,
+            BackButtonDelay::Left3 => &registry::BACK_BUTTON_LEFT_THIRD_DELAY,
+            BackButtonDelay::Right1 => &registry::BACK_BUTTON_RIGHT_FIRST_DELAY,
+            BackButtonDelay::Right2 => &registry::BACK_BUTTON_RIGHT_SECOND_DELAY,
+            BackButtonDelay::Right3 => &registry::BACK_BUTTON_RIGHT_THIRD_DELAY,
+        }
+    }
+}
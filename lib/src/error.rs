@@ -0,0 +1,117 @@
+use std::fmt::{Display, Formatter};
+
+/// What went wrong, decided where the error is raised
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ErrorCategory {
+    Other,
+    NotFound,
+    Permission,
+    Busy,
+    Validation,
+    Protocol,
+    Io,
+}
+
+/// Error message with its [ErrorCategory]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub category: ErrorCategory,
+    pub message: String,
+    /// Field of the [registry](crate::controls_field::registry) the error is about
+    pub path: Option<String>,
+    /// Raised by the device or a transfer with it, rather than by a file or the input
+    pub device: bool,
+}
+
+impl Error {
+    pub fn new(category: ErrorCategory, message: impl Into<String>) -> Self {
+        Error {
+            category,
+            message: message.into(),
+            path: None,
+            device: false,
+        }
+    }
+
+    pub fn device(category: ErrorCategory, message: impl Into<String>) -> Self {
+        Error {
+            device: true,
+            ..Error::new(category, message)
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Error::new(ErrorCategory::NotFound, message)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Error::new(ErrorCategory::Validation, message)
+    }
+
+    pub fn protocol(message: impl Into<String>) -> Self {
+        Error::new(ErrorCategory::Protocol, message)
+    }
+
+    pub fn with_path(self, path: impl Into<String>) -> Self {
+        Error {
+            path: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Prefix the message, e.g. with the file it is about, keeping category and path
+    pub fn context(self, context: impl Display) -> Self {
+        Error {
+            message: format!("{}: {}", context, self.message),
+            ..self
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::new(ErrorCategory::Other, message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::new(ErrorCategory::Other, message)
+    }
+}
+
+impl From<rusb::Error> for Error {
+    fn from(e: rusb::Error) -> Self {
+        let category = match e {
+            rusb::Error::Access => ErrorCategory::Permission,
+            rusb::Error::Busy => ErrorCategory::Busy,
+            rusb::Error::NoDevice | rusb::Error::NotFound => ErrorCategory::NotFound,
+            rusb::Error::Io
+            | rusb::Error::Timeout
+            | rusb::Error::Pipe
+            | rusb::Error::Overflow
+            | rusb::Error::Interrupted
+            | rusb::Error::BadDescriptor => ErrorCategory::Protocol,
+            _ => ErrorCategory::Other,
+        };
+        Error::device(category, e.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        let category = match e.kind() {
+            std::io::ErrorKind::PermissionDenied => ErrorCategory::Permission,
+            _ => ErrorCategory::Io,
+        };
+        Error::new(category, e.to_string())
+    }
+}
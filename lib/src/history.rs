@@ -0,0 +1,131 @@
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_file, write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
+
+use crate::controls_field::format::ConfigFormat;
+use crate::controls_field::parse::ParseMode;
+use crate::controls_field::ControlsConfig;
+use crate::error::Error;
+
+/// The config of the device right before it was changed
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter)]
+#[serde(default)]
+pub struct Snapshot {
+    /// RFC 3339 timestamp of when the snapshot was taken
+    pub created: String,
+    /// Command line which changed the config
+    pub command: String,
+    pub config: ControlsConfig,
+}
+
+impl Snapshot {
+    pub fn new(config: ControlsConfig, command: String) -> Self {
+        Snapshot {
+            created: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            command,
+            config,
+        }
+    }
+
+    pub fn to_string_with(&self, format: ConfigFormat) -> Result<String, Error> {
+        format.serialize(self)
+    }
+}
+
+/// A bounded directory of [Snapshot]s, one JSON file each, entries are numbered from 1 for the newest
+pub struct History {
+    dir: PathBuf,
+    limit: usize,
+}
+
+impl History {
+    pub const DEFAULT_LIMIT: usize = 50;
+
+    pub fn new(dir: PathBuf, limit: usize) -> Self {
+        History { dir, limit }
+    }
+
+    /// `$XDG_STATE_HOME/gpd-controls/history`
+    pub fn open_default() -> Result<Self, Error> {
+        dirs::state_dir()
+            .map(|dir| {
+                History::new(
+                    dir.join("gpd-controls").join("history"),
+                    Self::DEFAULT_LIMIT,
+                )
+            })
+            .ok_or_else(|| Error::from("Could not determine the user state directory"))
+    }
+
+    /// Store `snapshot` as newest entry and drop the oldest beyond the limit
+    pub fn push(&self, snapshot: &Snapshot) -> Result<(), Error> {
+        create_dir_all(&self.dir).map_err(|e| Error::from(e).context(self.dir.display()))?;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| Error::from(e.to_string()))?
+            .as_nanos();
+        let path = self.dir.join(format!("{:032}.json", nanos));
+        write(&path, snapshot.to_string_with(ConfigFormat::Json)?)
+            .map_err(|e| Error::from(e).context(path.display()))?;
+        self.paths()?.iter().skip(self.limit).try_for_each(|path| {
+            remove_file(path).map_err(|e| Error::from(e).context(path.display()))
+        })
+    }
+
+    /// All snapshots, newest first
+    pub fn list(&self) -> Result<Vec<Snapshot>, Error> {
+        self.paths()?.iter().map(|path| load(path)).collect()
+    }
+
+    /// Entry `n`, `1` is the newest
+    pub fn get(&self, n: usize) -> Result<Snapshot, Error> {
+        load(&self.path(n)?)
+    }
+
+    pub fn remove(&self, n: usize) -> Result<(), Error> {
+        let path = self.path(n)?;
+        remove_file(&path).map_err(|e| Error::from(e).context(path.display()))
+    }
+
+    fn path(&self, n: usize) -> Result<PathBuf, Error> {
+        let paths = self.paths()?;
+        n.checked_sub(1)
+            .and_then(|i| paths.get(i))
+            .cloned()
+            .ok_or_else(|| match paths.len() {
+                0 => Error::not_found("History is empty"),
+                len => Error::not_found(format!("No history entry {}, there are 1..={}", n, len)),
+            })
+    }
+
+    /// Paths of all entries, newest first
+    fn paths(&self) -> Result<Vec<PathBuf>, Error> {
+        let entries = match read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Error::from(e).context(self.dir.display())),
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file() && ConfigFormat::from_path(path) == Some(ConfigFormat::Json)
+            })
+            .collect();
+        paths.sort();
+        paths.reverse();
+        Ok(paths)
+    }
+}
+
+fn load(path: &Path) -> Result<Snapshot, Error> {
+    let content = read_to_string(path).map_err(|e| Error::from(e).context(path.display()))?;
+    // Entries may be written by newer versions, unknown fields are not an error
+    ConfigFormat::Json
+        .deserialize(&content, ParseMode::Lenient)
+        .map_err(|e| e.context(path.display()))
+}
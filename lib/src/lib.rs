@@ -6,13 +6,19 @@ pub use lazy_static::lazy_static;
 pub use log;
 pub use regex;
 use regex::Regex;
+pub use rusb;
 pub use strum;
 
 use crate::controls_field::hid_usage_id_u8::HIDUsageIDu8;
 use crate::Radix::{Decimal, Hexadecimal};
 
+pub mod autoswitch;
+pub mod backup;
 pub mod controls_field;
 pub mod enums;
+pub mod error;
+pub mod history;
+pub mod profile;
 pub mod protocol;
 
 pub struct LoadArray<const N: usize> {
@@ -47,17 +53,17 @@ enum Radix {
 }
 
 pub fn parse_hex(s: &str) -> Result<HIDUsageIDu8, String> {
-    return if DECIMAL.is_match(s) {
+    if DECIMAL.is_match(s) {
         u8_from_str_radix(s, Decimal)
     } else if HEXADECIMAL.is_match(s) {
         u8_from_str_radix(s.trim_start_matches("0x"), Hexadecimal)
     } else {
         Err(format!("invalid digit found in string {}", s))
-    };
+    }
 }
 
 fn u8_from_str_radix(s: &str, n: Radix) -> Result<HIDUsageIDu8, String> {
-    return match u8::from_str_radix(
+    match u8::from_str_radix(
         s,
         match n {
             Radix::Decimal => 10,
@@ -80,5 +86,5 @@ fn u8_from_str_radix(s: &str, n: Radix) -> Result<HIDUsageIDu8, String> {
             )),
             _ => Err(e.to_string()),
         },
-    };
+    }
 }
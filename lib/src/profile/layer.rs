@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::controls_field::parse::ParseMode;
+use crate::controls_field::ControlsConfig;
+use crate::error::Error;
+
+/// A possibly partial [ControlsConfig], only the fields present are set by the layer
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConfigLayer {
+    fields: Map<String, Value>,
+}
+
+impl ConfigLayer {
+    /// A layer setting every field of `config`
+    pub fn full(config: &ControlsConfig) -> Self {
+        match serde_json::to_value(config) {
+            Ok(Value::Object(fields)) => ConfigLayer { fields },
+            _ => ConfigLayer::default(),
+        }
+    }
+
+    /// A layer setting only the fields of `config` which differ from `base`
+    pub fn difference(config: &ControlsConfig, base: &ControlsConfig) -> Self {
+        let (Ok(Value::Object(config)), Ok(Value::Object(base))) =
+            (serde_json::to_value(config), serde_json::to_value(base))
+        else {
+            return ConfigLayer::default();
+        };
+        ConfigLayer {
+            fields: difference(config, &base),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Merge this layer on top of `base`, unknown or illegal fields are handled by `mode`
+    pub fn apply_to(
+        &self,
+        base: &ControlsConfig,
+        mode: ParseMode,
+    ) -> Result<ControlsConfig, Error> {
+        base.merge_patch(&Value::Object(self.fields.clone()), mode)
+    }
+
+    /// Dotted paths of all fields set by this layer, e.g. `back_button.left.first`
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        collect_paths(&self.fields, "", &mut paths);
+        paths
+    }
+}
+
+fn difference(config: Map<String, Value>, base: &Map<String, Value>) -> Map<String, Value> {
+    config
+        .into_iter()
+        .filter_map(|(key, value)| match (value, base.get(&key)) {
+            (Value::Object(value), Some(Value::Object(base))) => {
+                let value = difference(value, base);
+                (!value.is_empty()).then_some((key, Value::Object(value)))
+            }
+            (value, Some(base)) if value == *base => None,
+            (value, _) => Some((key, value)),
+        })
+        .collect()
+}
+
+fn collect_paths(fields: &Map<String, Value>, prefix: &str, paths: &mut Vec<String>) {
+    fields.iter().for_each(|(key, value)| {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(fields) => collect_paths(fields, &path, paths),
+            _ => paths.push(path),
+        }
+    });
+}
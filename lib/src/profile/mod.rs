@@ -0,0 +1,63 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use serialize_display_adapter_macro_derive::PrettyJsonSerializeDisplayAdapter;
+
+use crate::controls_field::format::ConfigFormat;
+use crate::controls_field::parse::ParseMode;
+use crate::controls_field::{ControlsConfig, FirmwareVersion};
+use crate::error::Error;
+use crate::profile::layer::ConfigLayer;
+
+pub mod layer;
+pub mod preset;
+pub mod resolve;
+pub mod store;
+
+/// A named [ControlsConfig] with metadata about where it came from
+///
+/// A profile may extend another profile, then `config` only holds the fields it overrides.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PrettyJsonSerializeDisplayAdapter)]
+#[serde(default)]
+pub struct Profile {
+    pub description: Option<String>,
+    /// RFC 3339 timestamp of when the profile was captured
+    pub created: Option<String>,
+    /// Firmware the config was read from
+    pub firmware_version: Option<FirmwareVersion>,
+    /// Name of the profile this one is layered on top of
+    pub extends: Option<String>,
+    pub config: ConfigLayer,
+}
+
+impl Profile {
+    /// Capture `config` now
+    pub fn new(
+        config: ControlsConfig,
+        description: Option<String>,
+        firmware_version: Option<FirmwareVersion>,
+    ) -> Self {
+        Profile {
+            description,
+            created: Some(humantime::format_rfc3339_seconds(SystemTime::now()).to_string()),
+            firmware_version,
+            extends: None,
+            config: ConfigLayer::full(&config),
+        }
+    }
+
+    /// Parse a profile, the fields of `config` are checked against [ControlsConfig] by `mode`
+    pub fn from_str_with(s: &str, format: ConfigFormat, mode: ParseMode) -> Result<Self, Error> {
+        let profile: Profile = format.deserialize(s, mode)?;
+        profile
+            .config
+            .apply_to(&ControlsConfig::default(), mode)
+            .map_err(|e| e.context("config"))?;
+        Ok(profile)
+    }
+
+    pub fn to_string_with(&self, format: ConfigFormat) -> Result<String, Error> {
+        format.serialize(self)
+    }
+}
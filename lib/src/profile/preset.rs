@@ -0,0 +1,175 @@
+use crate::controls_field::back_button::{BackButtonConfig, SpecificBackButtonConfig};
+use crate::controls_field::keyboard_mouse::{
+    DirectionalPadConfig, KeyboardMouseConfig, LeftStickConfig,
+};
+use crate::controls_field::ControlsConfig;
+use crate::enums::hid_usage_id::HIDUsageID;
+use crate::enums::hid_usage_id::HIDUsageID::*;
+use crate::profile::layer::ConfigLayer;
+use crate::profile::Profile;
+
+/// A profile compiled into the library
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    build: fn() -> ControlsConfig,
+}
+
+impl Preset {
+    pub fn config(&self) -> ControlsConfig {
+        (self.build)()
+    }
+
+    pub fn profile(&self) -> Profile {
+        Profile {
+            description: Some(self.description.to_string()),
+            config: ConfigLayer::full(&self.config()),
+            ..Default::default()
+        }
+    }
+}
+
+pub const PRESETS: [Preset; 5] = [
+    Preset {
+        name: "factory",
+        description: "Everything mapped to No Function, same as `reset all`",
+        build: ControlsConfig::default,
+    },
+    Preset {
+        name: "wasd",
+        description: "WASD on the left stick, arrow keys on the D-pad, Space/Shift/E/R on ABXY",
+        build: wasd,
+    },
+    Preset {
+        name: "arrows",
+        description: "Arrow keys on the left stick and D-pad, Enter/Escape on A/B",
+        build: arrows,
+    },
+    Preset {
+        name: "emulator-hotkeys",
+        description: "RetroArch default hotkeys on the back buttons: L4 save state (F2), R4 load state (F4)",
+        build: emulator_hotkeys,
+    },
+    Preset {
+        name: "desktop",
+        description: "Desktop navigation: arrow keys, Enter/Escape/Tab/Space on ABXY, the GPD Mouse_UP and Mouse_Down keys (0xE8, 0xE9) on the left stick up and down, Super and Menu on the back buttons",
+        build: desktop,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.name == name)
+}
+
+fn arrow_pad() -> DirectionalPadConfig {
+    DirectionalPadConfig {
+        up: UpArrow.into(),
+        down: DownArrow.into(),
+        left: LeftArrow.into(),
+        right: RightArrow.into(),
+    }
+}
+
+fn single_key(key: HIDUsageID) -> SpecificBackButtonConfig {
+    SpecificBackButtonConfig {
+        first: key.into(),
+        ..Default::default()
+    }
+}
+
+fn wasd() -> ControlsConfig {
+    ControlsConfig {
+        keyboard_mouse: KeyboardMouseConfig {
+            directional_pad: arrow_pad(),
+            a: Space.into(),
+            b: KeyboardLeftShift.into(),
+            x: E.into(),
+            y: R.into(),
+            left_stick: LeftStickConfig {
+                up: W.into(),
+                down: S.into(),
+                left: A.into(),
+                right: D.into(),
+                push: KeyboardLeftControl.into(),
+            },
+            right_stick_push: V.into(),
+        },
+        ..Default::default()
+    }
+}
+
+fn arrows() -> ControlsConfig {
+    ControlsConfig {
+        keyboard_mouse: KeyboardMouseConfig {
+            directional_pad: arrow_pad(),
+            a: Enter.into(),
+            b: Escape.into(),
+            left_stick: LeftStickConfig {
+                up: UpArrow.into(),
+                down: DownArrow.into(),
+                left: LeftArrow.into(),
+                right: RightArrow.into(),
+                push: Enter.into(),
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn emulator_hotkeys() -> ControlsConfig {
+    ControlsConfig {
+        back_button: BackButtonConfig {
+            left: single_key(F2),
+            right: single_key(F4),
+        },
+        ..Default::default()
+    }
+}
+
+fn desktop() -> ControlsConfig {
+    ControlsConfig {
+        keyboard_mouse: KeyboardMouseConfig {
+            directional_pad: arrow_pad(),
+            a: Enter.into(),
+            b: Escape.into(),
+            x: Tab.into(),
+            y: Space.into(),
+            left_stick: LeftStickConfig {
+                up: MouseUp.into(),
+                down: MouseDown.into(),
+                push: KeyboardLeftGUI.into(),
+                ..Default::default()
+            },
+            right_stick_push: Application.into(),
+        },
+        back_button: BackButtonConfig {
+            left: single_key(KeyboardLeftGUI),
+            right: single_key(Application),
+        },
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PRESETS;
+
+    #[test]
+    fn presets_are_valid() {
+        PRESETS.iter().for_each(|preset| {
+            assert_eq!(preset.config().validate(), Ok(()), "preset {}", preset.name);
+        });
+    }
+
+    #[test]
+    fn preset_names_are_unique() {
+        PRESETS.iter().enumerate().for_each(|(i, preset)| {
+            assert!(
+                PRESETS[..i].iter().all(|other| other.name != preset.name),
+                "preset {} is defined twice",
+                preset.name
+            );
+        });
+    }
+}
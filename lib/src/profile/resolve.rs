@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+
+use crate::controls_field::parse::ParseMode;
+use crate::controls_field::ControlsConfig;
+use crate::error::Error;
+use crate::profile::Profile;
+
+/// Anything profiles can be looked up from by name
+pub trait ProfileSource {
+    fn load_profile(&self, name: &str, mode: ParseMode) -> Result<Profile, Error>;
+}
+
+/// A profile with its `extends` chain merged into a concrete config
+#[derive(Clone, Debug)]
+pub struct ResolvedProfile {
+    pub config: ControlsConfig,
+    /// Names of the layers, from the root base to the requested profile
+    pub chain: Vec<String>,
+    /// The layer which set each field, fields missing here keep their default
+    pub sources: BTreeMap<String, String>,
+}
+
+impl ResolvedProfile {
+    /// Name of the layer which set `path`, `None` if no layer set it
+    pub fn source(&self, path: &str) -> Option<&str> {
+        self.sources.get(path).map(|v| v.as_str())
+    }
+}
+
+/// Resolve profile `name` from `source`, following `extends` until a profile without base
+pub fn resolve<S: ProfileSource + ?Sized>(
+    source: &S,
+    name: &str,
+    mode: ParseMode,
+) -> Result<ResolvedProfile, Error> {
+    let mut layers: Vec<(String, Profile)> = Vec::new();
+    let mut next = Some(name.to_string());
+    while let Some(name) = next {
+        if let Some(start) = layers.iter().position(|(v, _)| *v == name) {
+            let cycle = layers[start..]
+                .iter()
+                .map(|(v, _)| v.as_str())
+                .chain([name.as_str()])
+                .collect::<Vec<&str>>()
+                .join(" -> ");
+            return Err(Error::validation(format!(
+                "Profile inheritance cycle: {}",
+                cycle
+            )));
+        }
+        let profile = source
+            .load_profile(&name, mode)
+            .map_err(|e| match layers.last() {
+                Some((child, _)) => Error {
+                    message: format!("{} (extended by {})", e, child),
+                    ..e
+                },
+                None => e,
+            })?;
+        next = profile.extends.clone();
+        layers.push((name, profile));
+    }
+
+    let mut resolved = ResolvedProfile {
+        config: ControlsConfig::default(),
+        chain: Vec::new(),
+        sources: BTreeMap::new(),
+    };
+    for (name, profile) in layers.into_iter().rev() {
+        resolved.config = profile
+            .config
+            .apply_to(&resolved.config, mode)
+            .map_err(|e| e.context(format!("Profile {}", name)))?;
+        profile.config.paths().into_iter().for_each(|path| {
+            resolved.sources.insert(path, name.clone());
+        });
+        resolved.chain.push(name);
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{resolve, ProfileSource};
+    use crate::controls_field::format::ConfigFormat;
+    use crate::controls_field::parse::ParseMode;
+    use crate::error::{Error, ErrorCategory};
+    use crate::profile::Profile;
+
+    /// Profiles parsed from JSON by name
+    struct Profiles(BTreeMap<&'static str, &'static str>);
+
+    impl ProfileSource for Profiles {
+        fn load_profile(&self, name: &str, mode: ParseMode) -> Result<Profile, Error> {
+            let s = self
+                .0
+                .get(name)
+                .ok_or_else(|| Error::not_found(format!("Profile {} not found", name)))?;
+            Profile::from_str_with(s, ConfigFormat::Json, mode)
+        }
+    }
+
+    fn profiles() -> Profiles {
+        Profiles(BTreeMap::from([
+            (
+                "base",
+                r#"{"config": {"dead_zones": {"left": {"center": 2}}}}"#,
+            ),
+            (
+                "child",
+                r#"{"extends": "base", "config": {"dead_zones": {"right": {"center": 3}}}}"#,
+            ),
+            ("a", r#"{"extends": "b"}"#),
+            ("b", r#"{"extends": "c"}"#),
+            ("c", r#"{"extends": "a"}"#),
+            ("orphan", r#"{"extends": "missing"}"#),
+        ]))
+    }
+
+    #[test]
+    fn layers_are_merged_from_the_base() {
+        let resolved = resolve(&profiles(), "child", ParseMode::Strict).unwrap();
+        assert_eq!(resolved.chain, ["base", "child"]);
+        assert_eq!(resolved.config.dead_zones.left.center.value(), 2);
+        assert_eq!(resolved.config.dead_zones.right.center.value(), 3);
+        assert_eq!(resolved.source("dead_zones.left.center"), Some("base"));
+        assert_eq!(resolved.source("dead_zones.right.center"), Some("child"));
+        assert_eq!(resolved.source("vibrate"), None);
+    }
+
+    #[test]
+    fn inheritance_cycle_is_rejected() {
+        let e = resolve(&profiles(), "a", ParseMode::Strict).unwrap_err();
+        assert_eq!(e.category, ErrorCategory::Validation);
+        assert_eq!(e.message, "Profile inheritance cycle: a -> b -> c -> a");
+    }
+
+    #[test]
+    fn missing_base_names_the_child() {
+        let e = resolve(&profiles(), "orphan", ParseMode::Strict).unwrap_err();
+        assert_eq!(e.category, ErrorCategory::NotFound);
+        assert_eq!(e.message, "Profile missing not found (extended by orphan)");
+    }
+}
@@ -0,0 +1,143 @@
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_file, rename, write};
+use std::path::{Path, PathBuf};
+
+use crate::controls_field::format::ConfigFormat;
+use crate::controls_field::parse::ParseMode;
+use crate::error::Error;
+use crate::profile::preset;
+use crate::profile::resolve::ProfileSource;
+use crate::profile::Profile;
+
+const EXTENSIONS: [&str; 4] = ["toml", "json", "yaml", "yml"];
+
+/// A directory of named profiles, one file per profile in any [ConfigFormat]
+pub struct ProfileStore {
+    dir: PathBuf,
+}
+
+impl ProfileStore {
+    pub fn new(dir: PathBuf) -> Self {
+        ProfileStore { dir }
+    }
+
+    /// `$XDG_CONFIG_HOME/gpd-controls/profiles`
+    pub fn open_default() -> Result<Self, Error> {
+        dirs::config_dir()
+            .map(|dir| ProfileStore::new(dir.join("gpd-controls").join("profiles")))
+            .ok_or_else(|| Error::from("Could not determine the user config directory"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Names of all stored profiles, sorted
+    pub fn list(&self) -> Result<Vec<String>, Error> {
+        let entries = match read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Error::from(e).context(self.dir.display())),
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && ConfigFormat::from_path(path).is_some())
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect();
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    /// Path of the existing file storing profile `name`
+    pub fn path(&self, name: &str) -> Result<PathBuf, Error> {
+        validate_name(name)?;
+        EXTENSIONS
+            .iter()
+            .map(|extension| self.dir.join(format!("{}.{}", name, extension)))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                Error::not_found(format!(
+                    "Profile {} not found in {}",
+                    name,
+                    self.dir.display()
+                ))
+            })
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.path(name).is_ok()
+    }
+
+    pub fn load(&self, name: &str, mode: ParseMode) -> Result<Profile, Error> {
+        let path = self.path(name)?;
+        let content = read_to_string(&path).map_err(|e| Error::from(e).context(path.display()))?;
+        Profile::from_str_with(&content, ConfigFormat::resolve(None, &path), mode)
+            .map_err(|e| e.context(path.display()))
+    }
+
+    /// Store `profile` as `name`, an existing profile is only replaced with `overwrite`
+    pub fn save(
+        &self,
+        name: &str,
+        profile: &Profile,
+        format: ConfigFormat,
+        overwrite: bool,
+    ) -> Result<PathBuf, Error> {
+        validate_name(name)?;
+        let existing = self.path(name).ok();
+        if existing.is_some() && !overwrite {
+            return Err(format!("Profile {} already exists", name).into());
+        }
+        create_dir_all(&self.dir).map_err(|e| Error::from(e).context(self.dir.display()))?;
+        let path = self.dir.join(format!("{}.{}", name, format.extension()));
+        write(&path, profile.to_string_with(format)?)
+            .map_err(|e| Error::from(e).context(path.display()))?;
+        if let Some(existing) = existing.filter(|existing| *existing != path) {
+            remove_file(&existing).map_err(|e| Error::from(e).context(existing.display()))?;
+        }
+        Ok(path)
+    }
+
+    pub fn remove(&self, name: &str) -> Result<(), Error> {
+        let path = self.path(name)?;
+        remove_file(&path).map_err(|e| Error::from(e).context(path.display()))
+    }
+
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), Error> {
+        let source = self.path(from)?;
+        validate_name(to)?;
+        if self.exists(to) {
+            return Err(format!("Profile {} already exists", to).into());
+        }
+        let extension = source
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("toml");
+        let target = self.dir.join(format!("{}.{}", to, extension));
+        rename(&source, &target).map_err(|e| Error::from(e).context(source.display()))
+    }
+}
+
+/// Stored profiles shadow built-in presets of the same name
+impl ProfileSource for ProfileStore {
+    fn load_profile(&self, name: &str, mode: ParseMode) -> Result<Profile, Error> {
+        match preset::find(name) {
+            Some(preset) if !self.exists(name) => Ok(preset.profile()),
+            _ => self.load(name, mode),
+        }
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), Error> {
+    if name.is_empty()
+        || name.starts_with('.')
+        || name.contains(|c: char| c == '/' || c == '\\' || c.is_control())
+    {
+        return Err(Error::validation(format!(
+            "Invalid profile name {:?}",
+            name
+        )));
+    }
+    Ok(())
+}
@@ -1,33 +1,61 @@
+use log::warn;
 use num_enum::IntoPrimitive;
-use rusb::{DeviceHandle, UsbContext};
+use rusb::UsbContext;
 
+use crate::error::{Error, ErrorCategory};
 use crate::protocol::raw::{get_report, set_report};
+use crate::protocol::Device;
 
+const HEADER_LENGTH: usize = 5;
+
+/// Send a read request and return the response, the whole exchange is retried on failure
 pub fn read_command<T: UsbContext>(
-    device: &DeviceHandle<T>,
+    device: &Device<T>,
     serial: ReadCommandMajorSerial,
-) -> Result<[u8; 65], String> {
+) -> Result<[u8; 65], Error> {
     let mut data: [u8; 33] = [0; 33];
     data[0] = 0x01;
     data[1] = 0xA5;
     data[3] = 0x5A;
-    match serial {
+    // Only responses of Major0 and Major2 echo the request header
+    let echo_header = match serial {
         ReadCommandMajorSerial::Major0 => {
             data[2] = 0x10;
             data[4] = 0xEF;
+            true
         }
         ReadCommandMajorSerial::Major1(minor) => {
             data[2] = 0x11;
             data[4] = 0xEE;
-            data[6] = minor.into()
+            data[6] = minor.into();
+            false
         }
         ReadCommandMajorSerial::Major2 => {
             data[2] = 0x12;
             data[4] = 0xED;
+            true
         }
-    }
-    set_report(device, data)?;
-    get_report(device)
+    };
+
+    let policy = &device.policy;
+    policy.retry("read command", || {
+        set_report(device, data)?;
+        policy.wait();
+        let response = get_report(device)?;
+        if !echo_header || response[..HEADER_LENGTH] == data[..HEADER_LENGTH] {
+            return Ok(response);
+        }
+        let message = format!(
+            "Response header {:02X?} does not match request header {:02X?}",
+            &response[..HEADER_LENGTH],
+            &data[..HEADER_LENGTH],
+        );
+        if !policy.retry_on_header_mismatch {
+            warn!("{}", message);
+            return Ok(response);
+        }
+        Err(Error::device(ErrorCategory::Protocol, message))
+    })
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -47,11 +75,12 @@ pub enum ReadCommandMajor1MinorSerial {
     Minor3 = 3,
 }
 
+/// Send a write request, retried on failure
 pub fn write_command<T: UsbContext>(
-    device: &DeviceHandle<T>,
+    device: &Device<T>,
     serial: WriteCommandMajorSerial,
     load: [u8; 25],
-) -> Result<(), String> {
+) -> Result<(), Error> {
     let mut data: [u8; 33] = [0; 33];
     data[0] = 0x01;
     data[1] = 0xA5;
@@ -68,7 +97,11 @@ pub fn write_command<T: UsbContext>(
         }
     }
     data[8..].copy_from_slice(&load);
-    set_report(device, data)
+    device
+        .policy
+        .retry("write command", || set_report(device, data))?;
+    device.policy.wait();
+    Ok(())
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -1,23 +1,38 @@
-use log::warn;
-use rusb::{DeviceHandle, UsbContext};
+use log::{debug, warn};
+use rusb::UsbContext;
 
-use crate::controls_field::back_button::{BackButtonConfig, SpecificBackButtonConfig};
-use crate::controls_field::dead_zones::{DeadZonesConfig, SpecificDeadZone};
+use crate::controls_field::back_button::{
+    BackButtonConfig, BackButtonDelay, SpecificBackButtonConfig,
+};
+use crate::controls_field::dead_zones::{DeadZone, DeadZonesConfig, SpecificDeadZone};
 use crate::controls_field::keyboard_mouse::{
     DirectionalPadConfig, KeyboardMouseConfig, LeftStickConfig,
 };
+use crate::controls_field::validation::ValidationPolicy;
 use crate::controls_field::Checksum;
 use crate::controls_field::{ControlsConfig, ControlsField, FirmwareVersion, Version};
 use crate::enums::Vibrate;
 use crate::enums::Vibrate::Disable;
+use crate::error::Error;
 use crate::protocol::command::{
     read_command, write_command, ReadCommandMajor1MinorSerial, ReadCommandMajorSerial,
     WriteCommandMajor1MinorSerial, WriteCommandMajorSerial,
 };
+use crate::protocol::Device;
+
+/// Bytes of every read page belonging to one write page
+pub const CHUNK_LENGTH: usize = 16;
+
+/// The write pages holding known fields, pages 2, 6 and 7 are left untouched
+pub const CONFIG_PAGES: [WriteCommandMajor1MinorSerial; 5] = [
+    WriteCommandMajor1MinorSerial::Minor0,
+    WriteCommandMajor1MinorSerial::Minor1,
+    WriteCommandMajor1MinorSerial::Minor3,
+    WriteCommandMajor1MinorSerial::Minor4,
+    WriteCommandMajor1MinorSerial::Minor5,
+];
 
-pub fn read_firmware_version<T: UsbContext>(
-    device: &DeviceHandle<T>,
-) -> Result<FirmwareVersion, String> {
+pub fn read_firmware_version<T: UsbContext>(device: &Device<T>) -> Result<FirmwareVersion, Error> {
     let load = read_command(device, ReadCommandMajorSerial::Major0)?;
     Ok(FirmwareVersion {
         gamepad_firmware: Version {
@@ -31,80 +46,85 @@ pub fn read_firmware_version<T: UsbContext>(
     })
 }
 
-pub fn read_config<T: UsbContext>(device: &DeviceHandle<T>) -> Result<ControlsConfig, String> {
-    let load0 = read_command(
-        device,
-        ReadCommandMajorSerial::Major1(ReadCommandMajor1MinorSerial::Minor0),
-    )?;
-    let load1 = read_command(
-        device,
-        ReadCommandMajorSerial::Major1(ReadCommandMajor1MinorSerial::Minor1),
-    )?;
-    Ok(ControlsConfig {
-        keyboard_mouse: KeyboardMouseConfig {
-            directional_pad: DirectionalPadConfig {
-                up: load0[0].into(),
-                down: load0[2].into(),
-                left: load0[4].into(),
-                right: load0[6].into(),
-            },
-            a: load0[8].into(),
-            b: load0[10].into(),
-            x: load0[12].into(),
-            y: load0[14].into(),
-            left_stick: LeftStickConfig {
-                up: load0[16].into(),
-                down: load0[18].into(),
-                left: load0[20].into(),
-                right: load0[22].into(),
-                push: load0[24].into(),
-            },
-            right_stick_push: load0[26].into(),
-        },
-        back_button: BackButtonConfig {
-            left: SpecificBackButtonConfig {
-                first: load0[50].into(),
-                second: load0[52].into(),
-                third: load0[54].into(),
-                forth: load0[56].into(),
-                first_delay: load1[16],
-                second_delay: load1[18],
-                third_delay: load1[20],
-            },
-            right: SpecificBackButtonConfig {
-                first: load0[58].into(),
-                second: load0[60].into(),
-                third: load0[62].into(),
-                forth: load1[0].into(),
-                first_delay: load1[24],
-                second_delay: load1[26],
-                third_delay: load1[28],
-            },
-        },
-        vibrate: Vibrate::try_from(load1[2]).unwrap_or_else(|e| {
-            warn!("{}", e);
-            warn!("Vibrate field is set to Vibrate::Disable");
-            Disable
-        }),
-        dead_zones: DeadZonesConfig {
-            left: SpecificDeadZone {
-                center: load1[8] as i8,
-                border: load1[9] as i8,
-            },
-            right: SpecificDeadZone {
-                center: load1[10] as i8,
-                border: load1[11] as i8,
-            },
-        },
-    })
+/// Data taken from other firmware may be interpreted differently by the device,
+/// this is an error unless `force` is set, then it is only logged
+pub fn check_firmware<T: UsbContext>(
+    device: &Device<T>,
+    expected: &FirmwareVersion,
+    force: bool,
+) -> Result<(), Error> {
+    let firmware = read_firmware_version(device)?;
+    if firmware == *expected {
+        return Ok(());
+    }
+    let message = format!(
+        "Data was taken from firmware {}, the device runs {}",
+        expected.summary(),
+        firmware.summary()
+    );
+    if !force {
+        return Err(Error::validation(message));
+    }
+    warn!("{}", message);
+    Ok(())
+}
+
+pub fn read_config<T: UsbContext>(device: &Device<T>) -> Result<ControlsConfig, Error> {
+    Ok(decode_pages(&read_write_pages(device)?))
 }
 
-pub fn read_checksum<T: UsbContext>(device: &DeviceHandle<T>) -> Result<Checksum, String> {
+/// The write pages as the device holds them, see [split_read_pages]
+pub fn read_write_pages<T: UsbContext>(device: &Device<T>) -> Result<[[u8; 25]; 8], Error> {
+    let read_pages = [
+        ReadCommandMajor1MinorSerial::Minor0,
+        ReadCommandMajor1MinorSerial::Minor1,
+    ]
+    .into_iter()
+    .map(|minor| read_command(device, ReadCommandMajorSerial::Major1(minor)))
+    .collect::<Result<Vec<[u8; 65]>, Error>>()?;
+    split_read_pages(&read_pages)
+}
+
+/// Every 64 byte read page holds four write pages in 16 byte chunks, so the first two read
+/// pages cover all write pages. Bytes 16 to 24 of a write payload are never read back and
+/// left zero.
+pub fn split_read_pages(read_pages: &[[u8; 65]]) -> Result<[[u8; 25]; 8], Error> {
+    if read_pages.len() < 2 {
+        return Err(Error::protocol(format!(
+            "Expected at least 2 read pages, got {}",
+            read_pages.len()
+        )));
+    }
+    let mut pages = [[0u8; 25]; 8];
+    pages.iter_mut().enumerate().for_each(|(i, page)| {
+        let offset = i % 4 * CHUNK_LENGTH;
+        page[..CHUNK_LENGTH].copy_from_slice(&read_pages[i / 4][offset..offset + CHUNK_LENGTH]);
+    });
+    Ok(pages)
+}
+
+/// Responses of all Major1 reads, the pages the config is decoded from
+pub fn read_config_pages<T: UsbContext>(device: &Device<T>) -> Result<Vec<[u8; 65]>, Error> {
+    [
+        ReadCommandMajor1MinorSerial::Minor0,
+        ReadCommandMajor1MinorSerial::Minor1,
+        ReadCommandMajor1MinorSerial::Minor2,
+        ReadCommandMajor1MinorSerial::Minor3,
+    ]
+    .into_iter()
+    .map(|minor| read_command(device, ReadCommandMajorSerial::Major1(minor)))
+    .collect()
+}
+
+pub fn read_checksum<T: UsbContext>(device: &Device<T>) -> Result<Checksum, Error> {
     let load = read_command(device, ReadCommandMajorSerial::Major2)?;
-    Ok(u64::from_be_bytes(<[u8; 8]>::try_from(&load[24..32]).map_err(|e| e.to_string())?).into())
+    Ok(u64::from_be_bytes(
+        <[u8; 8]>::try_from(&load[24..32]).map_err(|e| Error::protocol(e.to_string()))?,
+    )
+    .into())
 }
 
-pub fn read_all<T: UsbContext>(device: &DeviceHandle<T>) -> Result<ControlsField, String> {
+pub fn read_all<T: UsbContext>(device: &Device<T>) -> Result<ControlsField, Error> {
     let config = read_config(device)?;
     Ok(ControlsField {
         firmware_version: read_firmware_version(device)?,
@@ -117,14 +137,30 @@ pub fn read_all<T: UsbContext>(device: &DeviceHandle<T>) -> Result<ControlsField
 }
 
 pub fn write_config<T: UsbContext>(
-    device: &DeviceHandle<T>,
+    device: &Device<T>,
     config: ControlsConfig,
-) -> Result<(), String> {
-    let mut load0 = [0u8; 25];
-    let mut load1 = [0u8; 25];
-    let mut load3 = [0u8; 25];
-    let mut load4 = [0u8; 25];
-    let mut load5 = [0u8; 25];
+    policy: ValidationPolicy,
+) -> Result<(), Error> {
+    config.check(policy)?;
+    // Bytes no field covers keep what the device holds, e.g. `2c 01` in Minor5
+    let pages = encode_pages_onto(&config, read_write_pages(device)?);
+    CONFIG_PAGES.into_iter().try_for_each(|minor| {
+        write_command(
+            device,
+            WriteCommandMajorSerial::Major1(minor),
+            pages[u8::from(minor) as usize],
+        )
+    })
+}
+
+/// Payloads of the write commands Minor0 to Minor7 for `config`
+pub fn encode_pages(config: &ControlsConfig) -> [[u8; 25]; 8] {
+    encode_pages_onto(config, [[0u8; 25]; 8])
+}
+
+/// Like [encode_pages], bytes no field covers are taken from `pages`
+pub fn encode_pages_onto(config: &ControlsConfig, mut pages: [[u8; 25]; 8]) -> [[u8; 25]; 8] {
+    let [load0, load1, _, load3, load4, load5, ..] = &mut pages;
 
     load0[0] = config.keyboard_mouse.directional_pad.up.into();
     load0[2] = config.keyboard_mouse.directional_pad.down.into();
@@ -152,46 +188,123 @@ pub fn write_config<T: UsbContext>(
 
     load4[0] = config.back_button.right.forth.into();
     load4[2] = config.vibrate.into();
-    load4[8] = config.dead_zones.left.center as u8;
-    load4[9] = config.dead_zones.left.border as u8;
-    load4[10] = config.dead_zones.right.center as u8;
-    load4[11] = config.dead_zones.right.border as u8;
-
-    load5[0] = config.back_button.left.first_delay;
-    load5[2] = config.back_button.left.second_delay;
-    load5[4] = config.back_button.left.third_delay;
-    load5[8] = config.back_button.right.first_delay;
-    load5[10] = config.back_button.right.second_delay;
-    load5[12] = config.back_button.right.third_delay;
-
-    write_command(
-        device,
-        WriteCommandMajorSerial::Major1(WriteCommandMajor1MinorSerial::Minor0),
-        load0,
-    )?;
-    write_command(
-        device,
-        WriteCommandMajorSerial::Major1(WriteCommandMajor1MinorSerial::Minor1),
-        load1,
-    )?;
-    write_command(
-        device,
-        WriteCommandMajorSerial::Major1(WriteCommandMajor1MinorSerial::Minor3),
-        load3,
-    )?;
-    write_command(
-        device,
-        WriteCommandMajorSerial::Major1(WriteCommandMajor1MinorSerial::Minor4),
-        load4,
-    )?;
-    write_command(
-        device,
-        WriteCommandMajorSerial::Major1(WriteCommandMajor1MinorSerial::Minor5),
-        load4,
-    )?;
-    Ok(())
+    load4[8] = config.dead_zones.left.center.into();
+    load4[9] = config.dead_zones.left.border.into();
+    load4[10] = config.dead_zones.right.center.into();
+    load4[11] = config.dead_zones.right.border.into();
+
+    load5[0] = config.back_button.left.first_delay.into();
+    load5[2] = config.back_button.left.second_delay.into();
+    load5[4] = config.back_button.left.third_delay.into();
+    load5[8] = config.back_button.right.first_delay.into();
+    load5[10] = config.back_button.right.second_delay.into();
+    load5[12] = config.back_button.right.third_delay.into();
+
+    pages
+}
+
+/// The config held by the write pages Minor0 to Minor7, the inverse of [encode_pages]
+pub fn decode_pages(pages: &[[u8; 25]; 8]) -> ControlsConfig {
+    let [load0, load1, _, load3, load4, load5, ..] = pages;
+    ControlsConfig {
+        keyboard_mouse: KeyboardMouseConfig {
+            directional_pad: DirectionalPadConfig {
+                up: load0[0].into(),
+                down: load0[2].into(),
+                left: load0[4].into(),
+                right: load0[6].into(),
+            },
+            a: load0[8].into(),
+            b: load0[10].into(),
+            x: load0[12].into(),
+            y: load0[14].into(),
+            left_stick: LeftStickConfig {
+                up: load1[0].into(),
+                down: load1[2].into(),
+                left: load1[4].into(),
+                right: load1[6].into(),
+                push: load1[8].into(),
+            },
+            right_stick_push: load1[10].into(),
+        },
+        back_button: BackButtonConfig {
+            left: SpecificBackButtonConfig {
+                first: load3[2].into(),
+                second: load3[4].into(),
+                third: load3[6].into(),
+                forth: load3[8].into(),
+                first_delay: BackButtonDelay::from_raw(load5[0]),
+                second_delay: BackButtonDelay::from_raw(load5[2]),
+                third_delay: BackButtonDelay::from_raw(load5[4]),
+            },
+            right: SpecificBackButtonConfig {
+                first: load3[10].into(),
+                second: load3[12].into(),
+                third: load3[14].into(),
+                forth: load4[0].into(),
+                first_delay: BackButtonDelay::from_raw(load5[8]),
+                second_delay: BackButtonDelay::from_raw(load5[10]),
+                third_delay: BackButtonDelay::from_raw(load5[12]),
+            },
+        },
+        vibrate: Vibrate::try_from(load4[2]).unwrap_or_else(|e| {
+            warn!("{}", e);
+            warn!("Vibrate field is set to Vibrate::Disable");
+            Disable
+        }),
+        dead_zones: DeadZonesConfig {
+            left: SpecificDeadZone {
+                center: DeadZone::from_raw(load4[8]),
+                border: DeadZone::from_raw(load4[9]),
+            },
+            right: SpecificDeadZone {
+                center: DeadZone::from_raw(load4[10]),
+                border: DeadZone::from_raw(load4[11]),
+            },
+        },
+    }
 }
 
-pub fn save<T: UsbContext>(device: &DeviceHandle<T>) -> Result<(), String> {
+pub fn save<T: UsbContext>(device: &Device<T>) -> Result<(), Error> {
     write_command(device, WriteCommandMajorSerial::Major3, [0u8; 25])
 }
+
+/// Write and save `config` only if it differs from the device, returns whether it was written
+///
+/// Saving writes the ROM, callers applying configs repeatedly should use this over [write_config].
+pub fn apply_config<T: UsbContext>(
+    device: &Device<T>,
+    config: ControlsConfig,
+    policy: ValidationPolicy,
+) -> Result<bool, Error> {
+    if read_config(device)? == config {
+        debug!("Config is already applied, skip writing");
+        return Ok(false);
+    }
+    write_config(device, config, policy)?;
+    save(device)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_pages, encode_pages, encode_pages_onto};
+    use crate::profile::preset::PRESETS;
+
+    #[test]
+    fn encode_onto_keeps_bytes_no_field_covers() {
+        let mut device = [[0u8; 25]; 8];
+        device[4][5] = 0xFF;
+        device[5][6..8].copy_from_slice(&[0x2C, 0x01]);
+        device[5][14..16].copy_from_slice(&[0x2C, 0x01]);
+        device[2][0] = 0x42;
+        let config = PRESETS[1].config();
+        let pages = encode_pages_onto(&config, device);
+        assert_eq!(decode_pages(&pages), config);
+        assert_eq!(pages[4][5], 0xFF);
+        assert_eq!(pages[5][6..8], [0x2C, 0x01]);
+        assert_eq!(pages[5][14..16], [0x2C, 0x01]);
+        assert_eq!(pages[2][0], 0x42);
+        assert_eq!(decode_pages(&encode_pages(&config)), config);
+    }
+}
@@ -0,0 +1,141 @@
+use rusb::UsbContext;
+
+use crate::controls_field::{ControlsConfig, FirmwareVersion, Version};
+use crate::error::Error;
+use crate::protocol::command::{write_command, WriteCommandMajorSerial};
+use crate::protocol::function::{
+    encode_pages, read_config_pages, read_firmware_version, split_read_pages, CONFIG_PAGES,
+};
+use crate::protocol::Device;
+
+pub const PAGE_COUNT: usize = 8;
+pub const PAGE_LENGTH: usize = 25;
+
+/// The payloads of the write commands Minor0 to Minor7
+///
+/// Read from the device bytes 16 to 24 of every page are zero, reads only return the first
+/// 16 bytes of a page, see [split_read_pages].
+///
+/// As file it is a 16 byte header followed by the pages:
+///
+/// | Offset | Length | Content                                                  |
+/// |--------|--------|----------------------------------------------------------|
+/// | 0      | 8      | magic `GPDCTRL\0`                                        |
+/// | 8      | 1      | format version, `1`                                      |
+/// | 9      | 4      | gamepad major, minor, keyboard mouse major, minor version |
+/// | 13     | 1      | page count, `8`                                          |
+/// | 14     | 1      | page length, `25`                                        |
+/// | 15     | 1      | reserved, `0`                                            |
+/// | 16     | 200    | pages Minor0 to Minor7                                   |
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageImage {
+    pub firmware_version: FirmwareVersion,
+    pub pages: [[u8; PAGE_LENGTH]; PAGE_COUNT],
+}
+
+impl PageImage {
+    pub const MAGIC: &'static [u8; 8] = b"GPDCTRL\0";
+    pub const FORMAT_VERSION: u8 = 1;
+    pub const HEADER_LENGTH: usize = 16;
+
+    /// The pages of `config`, bytes no field covers are zero, see [encode_pages]
+    pub fn from_config(config: &ControlsConfig, firmware_version: FirmwareVersion) -> Self {
+        PageImage {
+            firmware_version,
+            pages: encode_pages(config),
+        }
+    }
+
+    /// The pages as stored on the device, including bytes no known field covers
+    pub fn read<T: UsbContext>(device: &Device<T>) -> Result<Self, Error> {
+        Self::from_read_pages(read_firmware_version(device)?, &read_config_pages(device)?)
+    }
+
+    /// The write pages held by the responses of [read_config_pages], see [split_read_pages]
+    pub fn from_read_pages(
+        firmware_version: FirmwareVersion,
+        read_pages: &[[u8; 65]],
+    ) -> Result<Self, Error> {
+        Ok(PageImage {
+            firmware_version,
+            pages: split_read_pages(read_pages)?,
+        })
+    }
+
+    /// Write the [CONFIG_PAGES] as they are, without any validation, the caller saves
+    ///
+    /// Like [write_config](crate::protocol::function::write_config) pages 2, 6 and 7 are left
+    /// untouched, their bytes 16 to 24 are never read back and would be overwritten with zeros.
+    pub fn write_to<T: UsbContext>(&self, device: &Device<T>) -> Result<(), Error> {
+        CONFIG_PAGES.into_iter().try_for_each(|minor| {
+            write_command(
+                device,
+                WriteCommandMajorSerial::Major1(minor),
+                self.pages[u8::from(minor) as usize],
+            )
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let firmware = &self.firmware_version;
+        let mut bytes = Vec::with_capacity(Self::HEADER_LENGTH + PAGE_COUNT * PAGE_LENGTH);
+        bytes.extend_from_slice(Self::MAGIC);
+        bytes.extend_from_slice(&[
+            Self::FORMAT_VERSION,
+            firmware.gamepad_firmware.major_version,
+            firmware.gamepad_firmware.minor_version,
+            firmware.keyboard_mouse_firmware.major_version,
+            firmware.keyboard_mouse_firmware.minor_version,
+            PAGE_COUNT as u8,
+            PAGE_LENGTH as u8,
+            0,
+        ]);
+        self.pages
+            .iter()
+            .for_each(|page| bytes.extend_from_slice(page));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (header, data) = bytes
+            .split_at_checked(Self::HEADER_LENGTH)
+            .ok_or_else(|| Error::validation("Image is shorter than its header"))?;
+        if header[..8] != Self::MAGIC[..] {
+            return Err(Error::validation("Not a page image, magic GPDCTRL missing"));
+        }
+        if header[8] != Self::FORMAT_VERSION {
+            return Err(Error::validation(format!(
+                "Image format version {} is not supported, only {}",
+                header[8],
+                Self::FORMAT_VERSION
+            )));
+        }
+        if header[13] as usize != PAGE_COUNT
+            || header[14] as usize != PAGE_LENGTH
+            || data.len() != PAGE_COUNT * PAGE_LENGTH
+        {
+            return Err(Error::validation(format!(
+                "Image must hold {} pages of {} bytes",
+                PAGE_COUNT, PAGE_LENGTH
+            )));
+        }
+        let mut pages = [[0u8; PAGE_LENGTH]; PAGE_COUNT];
+        pages
+            .iter_mut()
+            .zip(data.chunks_exact(PAGE_LENGTH))
+            .for_each(|(page, chunk)| page.copy_from_slice(chunk));
+        Ok(PageImage {
+            firmware_version: FirmwareVersion {
+                gamepad_firmware: Version {
+                    major_version: header[9],
+                    minor_version: header[10],
+                },
+                keyboard_mouse_firmware: Version {
+                    major_version: header[11],
+                    minor_version: header[12],
+                },
+            },
+            pages,
+        })
+    }
+}
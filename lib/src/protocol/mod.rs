@@ -9,31 +9,61 @@ use rusb::constants::{
 use rusb::ffi::{libusb_context, libusb_set_log_cb};
 use rusb::{DeviceHandle, GlobalContext, LogLevel, UsbContext};
 
+use crate::error::{Error, ErrorCategory};
+use crate::protocol::policy::TransferPolicy;
+
 pub mod command;
 pub mod function;
+pub mod image;
+pub mod policy;
 pub mod raw;
 
-const VENDOR_ID: u16 = 0x2F24;
-const PRODUCT_ID: u16 = 0x0135;
+pub const VENDOR_ID: u16 = 0x2F24;
+pub const PRODUCT_ID: u16 = 0x0135;
 
-pub fn find() -> Result<DeviceHandle<GlobalContext>, String> {
-    rusb::open_device_with_vid_pid(VENDOR_ID, PRODUCT_ID).map_or_else(|| {
-        Err(format!("Could not find device with idVendor {} and idProduct {}\n\n{}\n\nOr apply udev rule {}", VENDOR_ID, PRODUCT_ID,
-                    "Do you run as root or Administrator?".red().bold(),
-                    r#"SUBSYSTEM=="usb", ATTRS{idVendor}=="2f24", ATTRS{idProduct}=="0135", MODE="0666", GROUP="plugdev""#.italic()))
-    }, |device| {
-        (|| -> Result<DeviceHandle<GlobalContext>, String> {
-            device.reset().map_err(|e| e.to_string())?;
-            Ok(device)
-        })()
-    })
+/// An opened device and the policy applied to its transfers
+pub struct Device<T: UsbContext = GlobalContext> {
+    pub handle: DeviceHandle<T>,
+    pub policy: TransferPolicy,
+}
+
+impl<T: UsbContext> Device<T> {
+    pub fn new(handle: DeviceHandle<T>, policy: TransferPolicy) -> Self {
+        Device { handle, policy }
+    }
+}
+
+pub fn find(policy: TransferPolicy) -> Result<Device, Error> {
+    let devices = rusb::devices()?;
+    let device = devices.iter().find(|device| {
+        device.device_descriptor().is_ok_and(|descriptor| {
+            descriptor.vendor_id() == VENDOR_ID && descriptor.product_id() == PRODUCT_ID
+        })
+    });
+    let Some(device) = device else {
+        return Err(Error::device(
+            ErrorCategory::NotFound,
+            format!(
+                "Could not find device with idVendor {:04x} and idProduct {:04x}",
+                VENDOR_ID, PRODUCT_ID
+            ),
+        ));
+    };
+    let handle = device.open().map_err(|e| match e {
+        rusb::Error::Access => Error::device(ErrorCategory::Permission, format!("{}\n\n{}\n\nOr apply udev rule {}", e,
+                                       "Do you run as root or Administrator?".red().bold(),
+                                       r#"SUBSYSTEM=="usb", ATTRS{idVendor}=="2f24", ATTRS{idProduct}=="0135", MODE="0666", GROUP="plugdev""#.italic())),
+        e => e.into(),
+    })?;
+    handle.reset()?;
+    Ok(Device::new(handle, policy))
 }
 
 fn process_kernel_driver<T: UsbContext>(
     device: &mut DeviceHandle<T>,
     attach_or_detach: bool,
-) -> Result<(), String> {
-    return if rusb::supports_detach_kernel_driver() {
+) -> Result<(), Error> {
+    if rusb::supports_detach_kernel_driver() {
         (|| -> Result<(), rusb::Error> {
             device.set_auto_detach_kernel_driver(true)?;
             let num = device.device().device_descriptor()?.num_configurations();
@@ -59,15 +89,16 @@ fn process_kernel_driver<T: UsbContext>(
             });
             Ok(())
         })()
-        .map_err(|e| e.to_string())
+        .map_err(Error::from)
     } else {
         info!("libusb: Not support detaching the kernel driver");
-        return Ok(());
-    };
+        Ok(())
+    }
 }
 
-pub fn detach_kernel_driver<T: UsbContext>(device: DeviceHandle<T>) -> Result<(), String> {
-    return if rusb::supports_detach_kernel_driver() {
+pub fn detach_kernel_driver<T: UsbContext>(device: Device<T>) -> Result<(), Error> {
+    let device = device.handle;
+    if rusb::supports_detach_kernel_driver() {
         (|| -> Result<(), rusb::Error> {
             let num = device.device().device_descriptor()?.num_configurations();
             (0..num).for_each(|i| {
@@ -87,19 +118,19 @@ pub fn detach_kernel_driver<T: UsbContext>(device: DeviceHandle<T>) -> Result<()
             });
             Ok(())
         })()
-        .map_err(|e| e.to_string())
+        .map_err(Error::from)
     } else {
         info!("libusb: Not support detaching the kernel driver");
-        return Ok(());
-    };
+        Ok(())
+    }
 }
 
-pub fn connect<T: UsbContext>(device: &mut DeviceHandle<T>) -> Result<(), String> {
-    process_kernel_driver(device, true)
+pub fn connect<T: UsbContext>(device: &mut Device<T>) -> Result<(), Error> {
+    process_kernel_driver(&mut device.handle, true)
 }
 
-pub fn disconnect<T: UsbContext>(mut device: DeviceHandle<T>) -> Result<(), String> {
-    process_kernel_driver(&mut device, false)
+pub fn disconnect<T: UsbContext>(mut device: Device<T>) -> Result<(), Error> {
+    process_kernel_driver(&mut device.handle, false)
 }
 
 extern "system" fn static_log_callback(_: *mut libusb_context, level: c_int, text: *mut c_void) {
@@ -0,0 +1,58 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use log::warn;
+
+use crate::error::Error;
+
+/// Timing and retry behaviour applied to every control transfer of a [Device](super::Device)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TransferPolicy {
+    /// Timeout of a single `SET_REPORT` or `GET_REPORT` transfer
+    pub timeout: Duration,
+    /// How many times a failed command is repeated before giving up
+    pub retries: u8,
+    /// Delay between a request and the following `GET_REPORT` or command,
+    /// gives the EC time to prepare its response, e.g. right after resume
+    pub command_delay: Duration,
+    /// Repeat the read command when the response does not echo the request header
+    pub retry_on_header_mismatch: bool,
+}
+
+impl Default for TransferPolicy {
+    fn default() -> Self {
+        TransferPolicy {
+            timeout: Duration::from_secs(1),
+            retries: 2,
+            command_delay: Duration::from_millis(5),
+            retry_on_header_mismatch: true,
+        }
+    }
+}
+
+impl TransferPolicy {
+    pub(crate) fn wait(&self) {
+        if !self.command_delay.is_zero() {
+            sleep(self.command_delay);
+        }
+    }
+
+    pub(crate) fn retry<R>(
+        &self,
+        name: &str,
+        mut f: impl FnMut() -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        let mut attempt = 0u8;
+        loop {
+            match f() {
+                Ok(v) => return Ok(v),
+                Err(e) if attempt < self.retries => {
+                    attempt += 1;
+                    warn!("{}: {}, retrying ({}/{})", name, e, attempt, self.retries);
+                    self.wait();
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
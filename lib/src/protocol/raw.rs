@@ -1,10 +1,13 @@
-use std::time::Duration;
-
-use log::debug;
-use rusb::{DeviceHandle, UsbContext};
+use log::{debug, warn};
+use rusb::UsbContext;
 
+use crate::error::{Error, ErrorCategory};
+use crate::protocol::Device;
 use crate::LoadArray;
 
+/// Payload length of every documented response, bytes 0 to 63
+pub const REPORT_LENGTH: usize = 64;
+
 #[derive(Debug)]
 struct ReportHeader {
     request_type: u8,
@@ -27,42 +30,67 @@ const GET_REPORT_HEADER: ReportHeader = ReportHeader {
     index: 2,
 };
 
-pub fn set_report<T: UsbContext>(device: &DeviceHandle<T>, data: [u8; 33]) -> Result<(), String> {
+/// A single `SET_REPORT` transfer, retries are left to the command sent
+pub fn set_report<T: UsbContext>(device: &Device<T>, data: [u8; 33]) -> Result<(), Error> {
     debug!(
         "SET_REPORT: {:#X}",
         <[u8; 33] as Into<LoadArray<33>>>::into(data)
     );
-    device
-        .write_control(
-            SET_REPORT_HEADER.request_type,
-            SET_REPORT_HEADER.request,
-            SET_REPORT_HEADER.value,
-            SET_REPORT_HEADER.index,
-            &data,
-            Duration::from_secs(1),
-        )
-        .map_or_else(|e| Err(e.to_string()), |_| Ok(()))
+    let length = device.handle.write_control(
+        SET_REPORT_HEADER.request_type,
+        SET_REPORT_HEADER.request,
+        SET_REPORT_HEADER.value,
+        SET_REPORT_HEADER.index,
+        &data,
+        device.policy.timeout,
+    )?;
+    if length < data.len() {
+        return Err(Error::device(
+            ErrorCategory::Protocol,
+            format!("Short write, sent {} of {} bytes", length, data.len()),
+        ));
+    }
+    Ok(())
 }
 
-pub fn get_report<T: UsbContext>(device: &DeviceHandle<T>) -> Result<[u8; 65], String> {
+fn read_report<T: UsbContext>(device: &Device<T>) -> Result<([u8; 65], usize), Error> {
     let mut data: [u8; 65] = [0; 65];
     device
+        .handle
         .read_control(
             GET_REPORT_HEADER.request_type,
             GET_REPORT_HEADER.request,
             GET_REPORT_HEADER.value,
             GET_REPORT_HEADER.index,
             &mut data,
-            Duration::from_secs(1),
+            device.policy.timeout,
         )
         .map_or_else(
-            |e| Err(e.to_string()),
-            |_| {
+            |e| Err(e.into()),
+            |length| {
                 debug!(
-                    "GET_REPORT: {:X}",
-                    <[u8; 65] as Into<LoadArray<65>>>::into(data)
+                    "GET_REPORT: {:X}({} bytes)",
+                    <[u8; 65] as Into<LoadArray<65>>>::into(data),
+                    length
                 );
-                Ok(data)
+                Ok((data, length))
             },
         )
 }
+
+/// Read a report, bytes missing from a response shorter than [REPORT_LENGTH] are left zero
+///
+/// A short read is only logged, it is not known yet whether the device ever returns one.
+pub fn get_report<T: UsbContext>(device: &Device<T>) -> Result<[u8; 65], Error> {
+    let (data, length) = read_report(device)?;
+    if length < REPORT_LENGTH {
+        warn!("Short read, received {} of {} bytes", length, REPORT_LENGTH);
+    }
+    Ok(data)
+}
+
+/// Read a report and return only the bytes actually received
+pub fn get_report_partial<T: UsbContext>(device: &Device<T>) -> Result<Vec<u8>, Error> {
+    let (data, length) = read_report(device)?;
+    Ok(data[..length].to_vec())
+}
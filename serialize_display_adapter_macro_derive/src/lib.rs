@@ -7,14 +7,12 @@
 //!     name: &'a str,
 //!     age: u8,
 //! }
-//! fn main() {
-//!     let name = "root";
-//!     let demo = Demo {
-//!         name: name.as_ref(),
-//!         age: 42,
-//!     };
-//!     print!("{}", demo)
-//! }
+//! let name = "root";
+//! let demo = Demo {
+//!     name: name.as_ref(),
+//!     age: 42,
+//! };
+//! print!("{}", demo)
 //!```
 //!
 //! Should print: